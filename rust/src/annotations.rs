@@ -0,0 +1,405 @@
+//! Opt-in preservation of comments (`# ...`) as annotations on the node that
+//! follows them.
+//!
+//! [LSD::parse] throws comments away inside its whitespace-skipping
+//! (`read_nws`), so a formatting-preserving tool built on this crate has
+//! nothing to work with. [LSD::parse_with_annotations] runs the same grammar
+//! but also collects each run of leading `#...` comments into a side table
+//! keyed by the path of the node they precede, so a writer can re-emit them
+//! later. The default [LSD::parse] path is untouched and pays nothing for
+//! this - no comment [String] is ever allocated there.
+//!
+//! This reuses the crate root's [Reader]/[peek]/[read_iws] and the grammar
+//! functions that don't care whether comments are being collected
+//! (`read_value`, `read_key_path`, `read_list_value`, and what they're built
+//! from) rather than forking them - `Reader`'s `X` side channel carries the
+//! in-progress [Comments] here. Only the functions that actually differ
+//! (attaching comments to a path as each node is read) are duplicated from
+//! `lib.rs`.
+
+use std::io;
+use std::io::BufReader;
+use std::io::Read;
+
+use indexmap::IndexMap;
+use utf8_chars::BufReadCharsExt;
+
+use crate::peek;
+use crate::read_iws;
+use crate::read_key_path;
+use crate::read_list_value;
+use crate::read_value;
+use crate::DuplicateKeyPolicy;
+use crate::KeyPathBuf;
+use crate::KeyPathPart;
+use crate::Level;
+use crate::List;
+use crate::ParseError;
+use crate::ParseErrorKind;
+use crate::Reader;
+use crate::Span;
+use crate::LSD;
+
+/// Leading comments collected per node, keyed by that node's full path from
+/// the root.
+pub type CommentTable = IndexMap<KeyPathBuf, Vec<String>>;
+
+impl LSD {
+    /// Parse an [LSD], additionally returning a [CommentTable] of every
+    /// leading `#...` comment, keyed by the path of the node it precedes.
+    ///
+    /// `duplicate_keys` resolves a key that already exists in a level, same
+    /// as [crate::LSDParser::duplicate_keys] - pass
+    /// [DuplicateKeyPolicy::Error] to match [LSD::parse]'s default.
+    pub fn parse_with_annotations(
+        stream: impl Read,
+        duplicate_keys: DuplicateKeyPolicy,
+    ) -> Result<(LSD, CommentTable), ParseError> {
+        use ParseErrorKind::*;
+
+        let mut reader = BufReader::new(stream);
+        let stream = &mut Reader::new_with(
+            reader
+                .chars(),
+            Comments::new(),
+        );
+
+        let mut path = KeyPathBuf::new();
+
+        read_nws(stream)?;
+
+        if let Some(level) = read_level(stream, &mut path, duplicate_keys)? {
+            read_nws(stream)?;
+
+            if peek(stream)?.is_some() {
+                return Err(stream.error(UnexpectedCharAtFileEnd));
+            }
+
+            return Ok((LSD::Level(level), stream.extra.take()));
+        };
+
+        if let Some(list) = read_list(stream, &mut path, duplicate_keys)? {
+            read_nws(stream)?;
+
+            if peek(stream)?.is_some() {
+                return Err(stream.error(UnexpectedCharAtFileEnd));
+            }
+
+            return Ok((LSD::List(list), stream.extra.take()));
+        };
+
+        let level = read_level_inner(stream, false, &mut path, duplicate_keys)?;
+        Ok((LSD::Level(level), stream.extra.take()))
+    }
+}
+
+struct Comments {
+    table: CommentTable,
+    pending: Vec<String>,
+}
+
+impl Comments {
+    fn new() -> Self {
+        Comments {
+            table: CommentTable::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Hand the currently pending comments over to `path`, if any were
+    /// collected since the last call.
+    fn attach(&mut self, path: &KeyPathBuf) {
+        if self
+            .pending
+            .is_empty()
+        {
+            return;
+        }
+
+        self.table
+            .insert(path.clone(), std::mem::take(&mut self.pending));
+    }
+
+    fn take(&mut self) -> CommentTable {
+        // anything still pending belongs to nothing that follows it (e.g. a
+        // trailing comment at the end of the file) - drop it
+        self.pending
+            .clear();
+        std::mem::take(&mut self.table)
+    }
+}
+
+/// Same grammar as the default parser's `read_nws`, but each `#...` run gets
+/// appended to `stream.extra.pending` instead of being thrown away.
+fn read_nws(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>, Comments>,
+) -> Result<bool, ParseError> {
+    read_iws(stream)?;
+
+    let mut has_newline = false;
+    let mut in_comment: Option<String> = None;
+    loop {
+        // `stream` can't be touched again until this match's scrutinee
+        // temporary (holding a reborrow of `stream` inside `accept`) has gone
+        // out of scope, so any comment finished by this iteration is carried
+        // out via `finished_comment` and only pushed once the match is over.
+        let mut finished_comment = None;
+        let mut done = false;
+        match peek(stream)? {
+            Some(('\r' | '\n', accept)) => {
+                accept();
+                finished_comment = in_comment.take();
+                has_newline = true;
+            },
+            Some((ch, accept)) if in_comment.is_some() => {
+                accept();
+                in_comment
+                    .as_mut()
+                    .unwrap()
+                    .push(ch);
+                continue;
+            },
+            Some(('#', accept)) => {
+                accept();
+                in_comment = Some(String::new());
+                continue;
+            },
+            _ => done = true,
+        }
+
+        if let Some(comment) = finished_comment {
+            stream
+                .extra
+                .pending
+                .push(comment);
+        }
+
+        if done {
+            return Ok(has_newline);
+        }
+
+        read_iws(stream)?;
+    }
+}
+
+fn read_lsd(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>, Comments>,
+    value_ignore_char: Option<char>,
+    path: &mut KeyPathBuf,
+    duplicate_keys: DuplicateKeyPolicy,
+) -> Result<Option<LSD>, ParseError> {
+    if let Some(list) = read_list(stream, path, duplicate_keys)? {
+        return Ok(Some(LSD::List(list)));
+    }
+
+    if let Some(level) = read_level(stream, path, duplicate_keys)? {
+        return Ok(Some(LSD::Level(level)));
+    }
+
+    if let Some(value) = read_value(stream, value_ignore_char, true)? {
+        return Ok(Some(LSD::Value(value)));
+    }
+
+    Ok(None)
+}
+
+fn read_level(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>, Comments>,
+    path: &mut KeyPathBuf,
+    duplicate_keys: DuplicateKeyPolicy,
+) -> Result<Option<Level>, ParseError> {
+    match peek(stream)? {
+        Some(('{', accept)) => accept(),
+        _ => return Ok(None),
+    };
+
+    read_nws(stream)?;
+
+    Ok(Some(read_level_inner(
+        stream,
+        true,
+        path,
+        duplicate_keys,
+    )?))
+}
+
+fn read_level_inner(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>, Comments>,
+    level_ends_with_close: bool,
+    path: &mut KeyPathBuf,
+    duplicate_keys: DuplicateKeyPolicy,
+) -> Result<Level, ParseError> {
+    use ParseErrorKind::*;
+
+    let mut results = Level::default();
+    Ok(loop {
+        if level_ends_with_close {
+            if let Some(('}', accept)) = peek(stream)? {
+                accept();
+                break results;
+            }
+        }
+
+        let key_pos = stream.pos;
+        let key = match read_key_path(stream, true)? {
+            Some(key) => key,
+            None if level_ends_with_close => return Err(stream.error(ExpectedKeyOrEnd)),
+            None => return Ok(results),
+        };
+
+        read_nws(stream)?;
+
+        for part in &key {
+            path.push(KeyPathPart::from(part.clone()));
+        }
+        stream
+            .extra
+            .attach(path);
+
+        let lsd = read_lsd(stream, Some('}'), path, duplicate_keys)?
+            .ok_or_else(|| stream.error(ExpectedLSDAfterKey))?;
+
+        for _ in &key {
+            path.pop();
+        }
+
+        read_nws(stream)?;
+
+        fn merge_level(
+            insert_into: &mut Level,
+            level: Level,
+            err_pos: Span,
+            duplicate_keys: DuplicateKeyPolicy,
+        ) -> Result<(), ParseError> {
+            for (key, value) in level.into_iter() {
+                if matches!(value, LSD::Level(_)) {
+                    let LSD::Level(lvl) = value else {
+                        unreachable!()
+                    };
+                    match insert_into
+                        .entry(key)
+                        .or_insert_with(|| LSD::Level(Level::default()))
+                    {
+                        LSD::Value(_) | LSD::List(_) => return Err(ParseError {
+                            kind: KeyCollisionShouldBeLevelButIsNot,
+                            span: err_pos,
+                        }),
+                        LSD::Level(ref mut insert_into) =>
+                            merge_level(insert_into, lvl, err_pos, duplicate_keys)?,
+                    }
+                    continue;
+                }
+
+                match insert_into.entry(key.clone()) {
+                    indexmap::map::Entry::Vacant(entry) => {
+                        entry.insert(value);
+                    },
+                    indexmap::map::Entry::Occupied(mut entry) => match duplicate_keys {
+                        DuplicateKeyPolicy::Error => return Err(ParseError {
+                            kind: KeyCollisionKeyAlreadyExists(key),
+                            span: err_pos,
+                        }),
+                        DuplicateKeyPolicy::FirstWins => {},
+                        DuplicateKeyPolicy::LastWins => {
+                            entry.insert(value);
+                        },
+                        DuplicateKeyPolicy::MergeLists => match (entry.get_mut(), value) {
+                            (LSD::List(existing), LSD::List(new)) => existing.extend(new),
+                            (existing, value) => *existing = value,
+                        },
+                    },
+                }
+            }
+            Ok(())
+        }
+
+        let mut result = Level::new();
+        let mut insert_into = &mut result;
+
+        for (i, part) in key
+            .iter()
+            .enumerate()
+        {
+            let part = part
+                .as_str()
+                .into();
+
+            if key.len() - 1 == i {
+                insert_into.insert(part, lsd);
+                break;
+            }
+
+            insert_into = match insert_into
+                .entry(part)
+                .or_insert_with(|| LSD::Level(Level::default()))
+            {
+                LSD::Level(ref mut lvl) => lvl,
+                _ => unreachable!(),
+            }
+        }
+
+        merge_level(&mut results, result, key_pos, duplicate_keys)?;
+    })
+}
+
+fn read_list_lsd(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>, Comments>,
+    path: &mut KeyPathBuf,
+    duplicate_keys: DuplicateKeyPolicy,
+) -> Result<Option<LSD>, ParseError> {
+    if let Some(list) = read_list(stream, path, duplicate_keys)? {
+        return Ok(Some(LSD::List(list)));
+    }
+
+    if let Some(level) = read_level(stream, path, duplicate_keys)? {
+        return Ok(Some(LSD::Level(level)));
+    }
+
+    if let Some(value) = read_list_value(stream, true)? {
+        return Ok(Some(LSD::Value(value)));
+    }
+
+    Ok(None)
+}
+
+fn read_list(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>, Comments>,
+    path: &mut KeyPathBuf,
+    duplicate_keys: DuplicateKeyPolicy,
+) -> Result<Option<List>, ParseError> {
+    use ParseErrorKind::*;
+
+    match peek(stream)? {
+        Some(('[', accept)) => accept(),
+        _ => return Ok(None),
+    };
+
+    read_nws(stream)?;
+
+    let mut results = List::default();
+    let mut index = 0;
+    Ok(Some(loop {
+        match peek(stream)? {
+            Some((']', accept)) => {
+                accept();
+                break results;
+            },
+            _ => {},
+        }
+
+        path.push(KeyPathPart::Index(index));
+        stream
+            .extra
+            .attach(path);
+
+        results.push(
+            read_list_lsd(stream, path, duplicate_keys)?
+                .ok_or_else(|| stream.error(ExpectedListLSDOrEnd))?,
+        );
+
+        path.pop();
+        index += 1;
+
+        read_nws(stream)?;
+    }))
+}
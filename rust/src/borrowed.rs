@@ -0,0 +1,709 @@
+//! Zero-copy parsing of LSD straight out of an in-memory `&str`.
+//!
+//! [LSD::parse] always goes through a [Read](std::io::Read) char-by-char and
+//! allocates a fresh [String] for every word, key and value. When the whole
+//! document is already in memory, [LSD::parse_str] avoids essentially all of
+//! that: words and unescaped strings are returned as [Cow::Borrowed] slices
+//! of the input, and only values that actually contain a `\`-escape (or are
+//! glued together out of several word/string parts) allocate.
+//!
+//! The stream primitives here (`Source`/`peek`/`bump`) are a genuinely
+//! different design from [crate::Reader] - they work over byte offsets into
+//! the borrowed `&str` rather than an `io::Read` char iterator, which is what
+//! makes the zero-copy slicing possible - so they're not shared with
+//! `lib.rs`. The hex digit decoding helpers have no such reason to differ,
+//! so they're pulled from the crate root instead of redefined here.
+
+use std::borrow::Cow;
+
+use indexmap::IndexMap;
+
+use crate::u16_from_4_hex_chars;
+use crate::u8_from_2_hex_chars;
+use crate::DuplicateKeyPolicy;
+use crate::ParseError;
+use crate::ParseErrorKind;
+use crate::Span;
+use crate::LSD;
+
+/// Borrowed mirror of [Value](crate::Value) - either a slice straight out of
+/// the source, or (if it needed unescaping or gluing together) an owned
+/// [String].
+pub type ValueRef<'a> = Cow<'a, str>;
+
+/// Borrowed mirror of [List](crate::List).
+pub type ListRef<'a> = Vec<LSDRef<'a>>;
+
+/// Borrowed mirror of [Level](crate::Level).
+pub type LevelRef<'a> = IndexMap<ValueRef<'a>, LSDRef<'a>>;
+
+/// Borrowed mirror of [LSD], returned by [LSD::parse_str].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LSDRef<'a> {
+    /// See [LSD::Value].
+    Value(ValueRef<'a>),
+
+    /// See [LSD::List].
+    List(ListRef<'a>),
+
+    /// See [LSD::Level].
+    Level(LevelRef<'a>),
+}
+
+impl<'a> LSDRef<'a> {
+    /// Convert this borrowed tree into an owned [LSD], allocating every
+    /// remaining borrowed slice.
+    pub fn into_owned(self) -> LSD {
+        match self {
+            LSDRef::Value(value) => LSD::Value(value.into_owned()),
+            LSDRef::List(list) => LSD::List(
+                list.into_iter()
+                    .map(LSDRef::into_owned)
+                    .collect(),
+            ),
+            LSDRef::Level(level) => LSD::Level(
+                level
+                    .into_iter()
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl LSD {
+    /// Parse an [LSD] out of a borrowed `&str`, avoiding allocation for
+    /// words and unescaped strings.
+    ///
+    /// `duplicate_keys` resolves a key that already exists in a level, same
+    /// as [crate::LSDParser::duplicate_keys] - pass [DuplicateKeyPolicy::Error] to
+    /// match [LSD::parse]'s default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lsdata::DuplicateKeyPolicy;
+    /// use lsdata::LSD;
+    ///
+    /// let lsd = LSD::parse_str("a 10", DuplicateKeyPolicy::Error).unwrap();
+    /// assert_eq!(lsd.into_owned(), LSD::parse(std::io::Cursor::new("a 10")).unwrap());
+    /// ```
+    pub fn parse_str(
+        input: &str,
+        duplicate_keys: DuplicateKeyPolicy,
+    ) -> Result<LSDRef<'_>, ParseError> {
+        use ParseErrorKind::*;
+
+        let stream = &mut Source::new(input);
+
+        read_nws(stream)?;
+
+        if let Some(level) = read_level(stream, 0, duplicate_keys)? {
+            read_nws(stream)?;
+
+            if peek(stream).is_some() {
+                return Err(stream.error(UnexpectedCharAtFileEnd));
+            }
+
+            return Ok(LSDRef::Level(level));
+        }
+
+        if let Some(list) = read_list(stream, 0, duplicate_keys)? {
+            read_nws(stream)?;
+
+            if peek(stream).is_some() {
+                return Err(stream.error(UnexpectedCharAtFileEnd));
+            }
+
+            return Ok(LSDRef::List(list));
+        }
+
+        Ok(LSDRef::Level(read_level_inner(
+            stream,
+            false,
+            0,
+            duplicate_keys,
+        )?))
+    }
+}
+
+/// Bound on how deeply nested lists/levels may be, so that adversarial input
+/// like `"[".repeat(200_000)` cannot blow the stack. [LSD::parse_str] takes
+/// no options, so unlike [crate::LSDParser::max_depth] this isn't
+/// configurable - it mirrors [crate::ParseOptions::default]'s bound instead.
+const MAX_DEPTH: usize = 128;
+
+/// A cursor into a borrowed `&str`, tracking both a byte offset (so ranges
+/// of the input can be sliced out directly) and a [Span] (for errors).
+struct Source<'a> {
+    input: &'a str,
+    byte_pos: usize,
+    pos: Span,
+}
+
+impl<'a> Source<'a> {
+    fn new(input: &'a str) -> Self {
+        Source {
+            input,
+            byte_pos: 0,
+            pos: Span::start(),
+        }
+    }
+
+    fn error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            kind,
+            span: self.pos,
+        }
+    }
+
+    fn error_at(&self, kind: ParseErrorKind, pos: Span) -> ParseError { ParseError { kind, span: pos } }
+
+    /// Mark the current byte offset, to later [Source::slice_from] it.
+    fn mark(&self) -> usize { self.byte_pos }
+
+    /// Borrow the input from a previously [Source::mark]ed offset up to (not
+    /// including) the current position.
+    fn slice_from(&self, start: usize) -> &'a str { &self.input[start..self.byte_pos] }
+}
+
+fn peek(stream: &Source<'_>) -> Option<char> {
+    stream.input[stream.byte_pos..]
+        .chars()
+        .next()
+}
+
+fn bump(stream: &mut Source<'_>) -> Option<char> {
+    let ch = peek(stream)?;
+    stream.byte_pos += ch.len_utf8();
+    stream
+        .pos
+        .advance(ch);
+    Some(ch)
+}
+
+fn read_iws(stream: &mut Source<'_>) {
+    while let Some(' ' | '\t') = peek(stream) {
+        bump(stream);
+    }
+}
+
+fn read_nws(stream: &mut Source<'_>) -> Result<bool, ParseError> {
+    read_iws(stream);
+
+    let mut has_newline = false;
+    let mut in_comment = false;
+    loop {
+        match peek(stream) {
+            Some('\r' | '\n') => {
+                bump(stream);
+                in_comment = false;
+                has_newline = true;
+            },
+            Some(_) if in_comment => {
+                bump(stream);
+                continue;
+            },
+            Some('#') => {
+                bump(stream);
+                in_comment = true;
+            },
+            _ => return Ok(has_newline),
+        }
+
+        read_iws(stream);
+    }
+}
+
+fn read_lsd<'a>(
+    stream: &mut Source<'a>,
+    value_ignore_char: Option<char>,
+    depth: usize,
+    duplicate_keys: DuplicateKeyPolicy,
+) -> Result<Option<LSDRef<'a>>, ParseError> {
+    if let Some(list) = read_list(stream, depth, duplicate_keys)? {
+        return Ok(Some(LSDRef::List(list)));
+    }
+
+    if let Some(level) = read_level(stream, depth, duplicate_keys)? {
+        return Ok(Some(LSDRef::Level(level)));
+    }
+
+    if let Some(value) = read_value(stream, value_ignore_char)? {
+        return Ok(Some(LSDRef::Value(value)));
+    }
+
+    Ok(None)
+}
+
+/// Join together the word/string parts of a value. Stays [Cow::Borrowed]
+/// when there is exactly one part and it didn't need unescaping; otherwise
+/// concatenates into an owned [String].
+fn read_value<'a>(
+    stream: &mut Source<'a>,
+    ignore_char: Option<char>,
+) -> Result<Option<ValueRef<'a>>, ParseError> {
+    let Some(mut result) = read_value_part(stream, ignore_char)? else {
+        return Ok(None);
+    };
+
+    loop {
+        let before_iws = stream.mark();
+        read_iws(stream);
+        let iws = stream.slice_from(before_iws);
+
+        match read_value_part(stream, ignore_char)? {
+            Some(part) => {
+                let joined = result.to_mut();
+                joined.push_str(iws);
+                joined.push_str(&part);
+            },
+            None => break,
+        }
+    }
+
+    Ok(Some(result))
+}
+
+fn read_value_part<'a>(
+    stream: &mut Source<'a>,
+    ignore_char: Option<char>,
+) -> Result<Option<ValueRef<'a>>, ParseError> {
+    if let Some(word) = read_word(stream, ignore_char) {
+        return Ok(Some(Cow::Borrowed(word)));
+    }
+
+    if let Some(string) = read_string(stream)? {
+        return Ok(Some(string));
+    }
+
+    Ok(None)
+}
+
+fn read_word<'a>(stream: &mut Source<'a>, ignore_char: Option<char>) -> Option<&'a str> {
+    let start = stream.mark();
+    loop {
+        match peek(stream) {
+            None | Some(' ' | '\t' | '\r' | '\n' | '\'' | '"' | '#') => break,
+            Some(ch) if Some(ch) == ignore_char => break,
+            Some(_) => {
+                bump(stream);
+            },
+        }
+    }
+    (stream.mark() > start).then(|| stream.slice_from(start))
+}
+
+/// Read a quoted string. Returns [Cow::Borrowed] when it contains no
+/// `\`-escapes (the common case), [Cow::Owned] otherwise.
+fn read_string<'a>(stream: &mut Source<'a>) -> Result<Option<ValueRef<'a>>, ParseError> {
+    use ParseErrorKind::*;
+
+    let start_pos = stream.pos;
+
+    let closing_char = match peek(stream) {
+        Some(ch @ ('"' | '\'')) => {
+            bump(stream);
+            ch
+        },
+        _ => return Ok(None),
+    };
+
+    macro_rules! bump_or_string_end {
+        () => {
+            bump(stream).ok_or_else(|| stream.error_at(UnexpectedStringEnd, start_pos))?
+        };
+    }
+
+    // scan first without allocating, betting on "no escapes" - the common case
+    let body_start = stream.mark();
+    loop {
+        match bump_or_string_end!() {
+            '\\' => break,
+            ch if ch == closing_char => {
+                return Ok(Some(Cow::Borrowed(
+                    &stream.slice_from(body_start)[..stream.mark() - body_start - ch.len_utf8()],
+                )));
+            },
+            _ => {},
+        }
+    }
+
+    // an escape showed up - fall back to building an owned string, including
+    // everything read so far verbatim
+    let mut result = stream
+        .slice_from(body_start)
+        .to_string();
+    result.truncate(result.len() - 1); // drop the '\\' we just consumed
+
+    // decode the escape specifier (the char right after a '\\') into `result`
+    macro_rules! read_escape_specifier {
+        () => {
+            match bump(stream).ok_or_else(|| stream.error(UnexpectedCharEscapeEnd))? {
+                '"' => result.push('"'),
+                '\\' => result.push('\\'),
+                '\'' => result.push('\''),
+                '0' => result.push('\0'),
+                'a' | 'A' => result.push('\x07'),
+                'b' | 'B' => result.push('\x08'),
+                't' | 'T' => result.push('\t'),
+                'n' | 'N' => result.push('\n'),
+                'v' | 'V' => result.push('\x0b'),
+                'f' | 'F' => result.push('\x0c'),
+                'r' | 'R' => result.push('\r'),
+                'x' | 'X' => {
+                    let first_byte =
+                        u8_from_2_hex_chars(bump_or_string_end!(), bump_or_string_end!())
+                            .map_err(|()| stream.error(UnexpectedCharInByteEscape))?;
+
+                    let mut bytes = vec![first_byte];
+
+                    for _ in 0..first_byte.leading_ones() {
+                        match (bump_or_string_end!(), bump_or_string_end!()) {
+                            ('\\', 'x' | 'X') => {},
+                            _ => return Err(stream.error(UnexpectedCharInByteEscape)),
+                        }
+
+                        bytes.push(
+                            u8_from_2_hex_chars(bump_or_string_end!(), bump_or_string_end!())
+                                .map_err(|()| stream.error(UnexpectedCharInByteEscape))?,
+                        )
+                    }
+
+                    result.push_str(
+                        &String::from_utf8(bytes)
+                            .map_err(|_| stream.error(UnexpectedCharInByteEscape))?,
+                    )
+                },
+                'u' | 'U' => {
+                    let first_surrogate = u16_from_4_hex_chars(
+                        bump_or_string_end!(),
+                        bump_or_string_end!(),
+                        bump_or_string_end!(),
+                        bump_or_string_end!(),
+                    )
+                    .map_err(|()| stream.error(UnexpectedCharInUnicodeEscape))?;
+
+                    // `read_escape_specifier!` is called both inside and
+                    // outside of a loop, so this can't use `continue` to skip
+                    // the low-surrogate lookup like `lib.rs`'s single-loop
+                    // version does - branch on it instead
+                    match char::decode_utf16([first_surrogate])
+                        .next()
+                        .unwrap()
+                    {
+                        Ok(ch) => result.push(ch),
+                        Err(_) => {
+                            match (bump_or_string_end!(), bump_or_string_end!()) {
+                                ('\\', 'u' | 'U') => {},
+                                _ => return Err(stream.error(UnexpectedCharInUnicodeEscape)),
+                            }
+
+                            let second_surrogate = u16_from_4_hex_chars(
+                                bump_or_string_end!(),
+                                bump_or_string_end!(),
+                                bump_or_string_end!(),
+                                bump_or_string_end!(),
+                            )
+                            .map_err(|()| stream.error(UnexpectedCharInUnicodeEscape))?;
+
+                            result.push(
+                                char::decode_utf16([first_surrogate, second_surrogate])
+                                    .next()
+                                    .unwrap()
+                                    .map_err(|_| stream.error(UnexpectedCharInUnicodeEscape))?,
+                            );
+                        },
+                    }
+                },
+                _ => return Err(stream.error(UnexpectedCharEscapeEnd)),
+            }
+        };
+    }
+
+    // the fast scan above already consumed the backslash that ended it, so
+    // the very next char is the escape specifier itself - decode it directly
+    // instead of going through the loop below, which would otherwise expect
+    // to see a fresh '\\' first
+    read_escape_specifier!();
+
+    loop {
+        match bump_or_string_end!() {
+            '\\' => read_escape_specifier!(),
+            ch if ch == closing_char => return Ok(Some(Cow::Owned(result))),
+            ch => result.push(ch),
+        }
+    }
+}
+
+fn read_level<'a>(
+    stream: &mut Source<'a>,
+    depth: usize,
+    duplicate_keys: DuplicateKeyPolicy,
+) -> Result<Option<LevelRef<'a>>, ParseError> {
+    use ParseErrorKind::*;
+
+    match peek(stream) {
+        Some('{') => {
+            bump(stream);
+        },
+        _ => return Ok(None),
+    }
+
+    if depth >= MAX_DEPTH {
+        return Err(stream.error(ExceededMaxDepth));
+    }
+
+    read_nws(stream)?;
+
+    Ok(Some(read_level_inner(
+        stream,
+        true,
+        depth + 1,
+        duplicate_keys,
+    )?))
+}
+
+fn read_level_inner<'a>(
+    stream: &mut Source<'a>,
+    level_ends_with_close: bool,
+    depth: usize,
+    duplicate_keys: DuplicateKeyPolicy,
+) -> Result<LevelRef<'a>, ParseError> {
+    use ParseErrorKind::*;
+
+    let mut results = LevelRef::default();
+    Ok(loop {
+        if level_ends_with_close && peek(stream) == Some('}') {
+            bump(stream);
+            break results;
+        }
+
+        let key_pos = stream.pos;
+        let key = match read_key_path(stream)? {
+            Some(key) => key,
+            None if level_ends_with_close => return Err(stream.error(ExpectedKeyOrEnd)),
+            None => return Ok(results),
+        };
+
+        read_nws(stream)?;
+
+        let lsd = read_lsd(stream, Some('}'), depth, duplicate_keys)?
+            .ok_or_else(|| stream.error(ExpectedLSDAfterKey))?;
+
+        read_nws(stream)?;
+
+        fn merge_level<'a>(
+            insert_into: &mut LevelRef<'a>,
+            level: LevelRef<'a>,
+            err_pos: Span,
+            duplicate_keys: DuplicateKeyPolicy,
+        ) -> Result<(), ParseError> {
+            for (key, value) in level.into_iter() {
+                if matches!(value, LSDRef::Level(_)) {
+                    let LSDRef::Level(lvl) = value else {
+                        unreachable!()
+                    };
+                    match insert_into
+                        .entry(key)
+                        .or_insert_with(|| LSDRef::Level(LevelRef::default()))
+                    {
+                        LSDRef::Value(_) | LSDRef::List(_) => return Err(ParseError {
+                            kind: KeyCollisionShouldBeLevelButIsNot,
+                            span: err_pos,
+                        }),
+                        LSDRef::Level(ref mut insert_into) =>
+                            merge_level(insert_into, lvl, err_pos, duplicate_keys)?,
+                    }
+                    continue;
+                }
+
+                match insert_into.entry(key.clone()) {
+                    indexmap::map::Entry::Vacant(entry) => {
+                        entry.insert(value);
+                    },
+                    indexmap::map::Entry::Occupied(mut entry) => match duplicate_keys {
+                        DuplicateKeyPolicy::Error => return Err(ParseError {
+                            kind: KeyCollisionKeyAlreadyExists(key.into_owned()),
+                            span: err_pos,
+                        }),
+                        DuplicateKeyPolicy::FirstWins => {},
+                        DuplicateKeyPolicy::LastWins => {
+                            entry.insert(value);
+                        },
+                        DuplicateKeyPolicy::MergeLists => match (entry.get_mut(), value) {
+                            (LSDRef::List(existing), LSDRef::List(new)) => existing.extend(new),
+                            (existing, value) => *existing = value,
+                        },
+                    },
+                }
+            }
+            Ok(())
+        }
+
+        let mut result = LevelRef::new();
+        let mut insert_into = &mut result;
+
+        for (i, part) in key
+            .iter()
+            .enumerate()
+        {
+            if key.len() - 1 == i {
+                insert_into.insert(part.clone(), lsd);
+                break;
+            }
+
+            insert_into = match insert_into
+                .entry(part.clone())
+                .or_insert_with(|| LSDRef::Level(LevelRef::default()))
+            {
+                LSDRef::Level(ref mut lvl) => lvl,
+                _ => unreachable!(),
+            }
+        }
+
+        merge_level(&mut results, result, key_pos, duplicate_keys)?;
+    })
+}
+
+fn read_key_word<'a>(stream: &mut Source<'a>) -> Option<&'a str> {
+    let start = stream.mark();
+    loop {
+        match peek(stream) {
+            None
+            | Some(
+                ' ' | '\t' | '\r' | '\n' | '\'' | '"' | '#' | '{' | '}' | '[' | ']' | '.',
+            ) => break,
+            Some(_) => {
+                bump(stream);
+            },
+        }
+    }
+    (stream.mark() > start).then(|| stream.slice_from(start))
+}
+
+fn read_key_part<'a>(stream: &mut Source<'a>) -> Result<Option<ValueRef<'a>>, ParseError> {
+    let mut result: Option<ValueRef<'a>> = None;
+    loop {
+        if let Some(word) = read_key_word(stream) {
+            match &mut result {
+                Some(result) => result
+                    .to_mut()
+                    .push_str(word),
+                None => result = Some(Cow::Borrowed(word)),
+            }
+            continue;
+        }
+
+        if let Some(string) = read_string(stream)? {
+            match &mut result {
+                Some(result) => result
+                    .to_mut()
+                    .push_str(&string),
+                None => result = Some(string),
+            }
+            continue;
+        }
+
+        break Ok(result);
+    }
+}
+
+fn read_key_path<'a>(stream: &mut Source<'a>) -> Result<Option<Vec<ValueRef<'a>>>, ParseError> {
+    use ParseErrorKind::*;
+
+    let mut result = vec![match read_key_part(stream)? {
+        Some(key_part) => key_part,
+        None => return Ok(None),
+    }];
+
+    while peek(stream) == Some('.') {
+        bump(stream);
+
+        result.push(
+            read_key_part(stream)?
+                .ok_or_else(|| stream.error(ExpectedKeyPartAfterKeySeparator))?,
+        );
+    }
+
+    Ok(Some(result))
+}
+
+fn read_list_lsd<'a>(
+    stream: &mut Source<'a>,
+    depth: usize,
+    duplicate_keys: DuplicateKeyPolicy,
+) -> Result<Option<LSDRef<'a>>, ParseError> {
+    if let Some(list) = read_list(stream, depth, duplicate_keys)? {
+        return Ok(Some(LSDRef::List(list)));
+    }
+
+    if let Some(level) = read_level(stream, depth, duplicate_keys)? {
+        return Ok(Some(LSDRef::Level(level)));
+    }
+
+    if let Some(value) = read_list_value(stream)? {
+        return Ok(Some(LSDRef::Value(value)));
+    }
+
+    Ok(None)
+}
+
+fn read_list_value<'a>(stream: &mut Source<'a>) -> Result<Option<ValueRef<'a>>, ParseError> {
+    let Some(mut result) = read_key_part(stream)? else {
+        return Ok(None);
+    };
+
+    loop {
+        let before_iws = stream.mark();
+        read_iws(stream);
+        let iws = stream.slice_from(before_iws);
+
+        match read_key_part(stream)? {
+            Some(part) => {
+                let joined = result.to_mut();
+                joined.push_str(iws);
+                joined.push_str(&part);
+            },
+            None => break,
+        }
+    }
+
+    Ok(Some(result))
+}
+
+fn read_list<'a>(
+    stream: &mut Source<'a>,
+    depth: usize,
+    duplicate_keys: DuplicateKeyPolicy,
+) -> Result<Option<ListRef<'a>>, ParseError> {
+    use ParseErrorKind::*;
+
+    match peek(stream) {
+        Some('[') => {
+            bump(stream);
+        },
+        _ => return Ok(None),
+    }
+
+    if depth >= MAX_DEPTH {
+        return Err(stream.error(ExceededMaxDepth));
+    }
+
+    read_nws(stream)?;
+
+    let mut results = ListRef::default();
+    Ok(Some(loop {
+        if peek(stream) == Some(']') {
+            bump(stream);
+            break results;
+        }
+
+        results.push(
+            read_list_lsd(stream, depth + 1, duplicate_keys)?
+                .ok_or_else(|| stream.error(ExpectedListLSDOrEnd))?,
+        );
+
+        read_nws(stream)?;
+    }))
+}
@@ -50,6 +50,27 @@ use implicit_clone::ImplicitClone;
 use indexmap::IndexMap;
 use utf8_chars::BufReadCharsExt;
 
+mod annotations;
+mod borrowed;
+mod recovery;
+#[cfg(feature = "serde")]
+mod serde_support;
+
+pub use annotations::CommentTable;
+pub use borrowed::LSDRef;
+pub use borrowed::LevelRef;
+pub use borrowed::ListRef;
+pub use borrowed::ValueRef;
+
+#[cfg(feature = "serde")]
+pub use serde_support::from_reader;
+#[cfg(feature = "serde")]
+pub use serde_support::from_str;
+#[cfg(feature = "serde")]
+pub use serde_support::to_string;
+#[cfg(feature = "serde")]
+pub use serde_support::Error as SerdeError;
+
 pub type Value = String;
 pub type List = Vec<LSD>;
 pub type Level = IndexMap<Value, LSD>;
@@ -83,9 +104,54 @@ impl PartialEq<LSD> for &LSD {
 // Parse
 //
 
-/// All errors thrown by the [LSD] parser.
+/// A byte offset into a source document, along with the 1-based line and
+/// column it corresponds to.
+///
+/// Columns and lines count Unicode scalar values, not bytes or grapheme
+/// clusters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Span {
+    /// 1-based line number.
+    pub line: usize,
+
+    /// 1-based column number.
+    pub col: usize,
+
+    /// 0-based byte offset from the start of the source.
+    pub offset: usize,
+}
+
+impl Span {
+    pub(crate) fn start() -> Self {
+        Span {
+            line: 1,
+            col: 1,
+            offset: 0,
+        }
+    }
+
+    pub(crate) fn advance(&mut self, ch: char) {
+        self.offset += ch.len_utf8();
+        if ch == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.col)
+    }
+}
+
+/// The kind of error thrown by the [LSD] parser, without a [Span].
+///
+/// See [ParseError] for the positioned version returned by [LSD::parse].
 #[derive(Debug)]
-pub enum ParseError {
+pub enum ParseErrorKind {
     /// [io::Error]s thrown during reading.
     ReadFailure(io::Error),
 
@@ -121,14 +187,32 @@ pub enum ParseError {
 
     /// Key repeated twice in the same level or after a merge.
     KeyCollisionKeyAlreadyExists(String),
+
+    /// A list/level nested deeper than [LSDParser::max_depth] allows.
+    ExceededMaxDepth,
 }
 
-impl From<io::Error> for ParseError {
-    fn from(value: io::Error) -> Self { Self::ReadFailure(value) }
+/// All errors thrown by the [LSD] parser, carrying the [Span] of the
+/// character that triggered them.
+///
+/// For [ParseErrorKind::UnexpectedStringEnd], [ParseError::span] is the start
+/// of the unterminated string/value rather than the end of the file, since
+/// that is the useful place to point a user at.
+#[derive(Debug)]
+pub struct ParseError {
+    /// What went wrong.
+    pub kind: ParseErrorKind,
+
+    /// Where it went wrong.
+    pub span: Span,
 }
 
 impl LSD {
-    /// Parse an [LSD] from a [Read] stream.
+    /// Parse an [LSD] from a [Read] stream, using the default (strict)
+    /// parser options.
+    ///
+    /// Shortcut for `LSD::parser().parse(stream)`. See [LSD::parser] for a
+    /// configurable parser, e.g. for parsing untrusted input.
     ///
     /// # Examples
     ///
@@ -149,45 +233,225 @@ impl LSD {
     ///     ])),
     /// );
     /// ```
-    pub fn parse(stream: impl Read) -> Result<LSD, ParseError> {
-        use ParseError::*;
+    pub fn parse(stream: impl Read) -> Result<LSD, ParseError> { LSDParser::default().parse(stream) }
+
+    /// Start building a configurable [LSDParser].
+    pub fn parser() -> LSDParser { LSDParser::default() }
+}
+
+//
+// Parser options
+//
+
+/// How a [LSDParser] should resolve a key that already exists in a level,
+/// whether from a literal duplicate key in the source or from two dotted
+/// key paths merging into the same level.
+///
+/// The policy only applies when the new value is not a [LSD::Level]. Two
+/// levels under the same key are always merged, and a level meeting an
+/// existing value or list always fails with
+/// [ParseErrorKind::KeyCollisionShouldBeLevelButIsNot], whatever the policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeyPolicy {
+    /// Fail to parse with [ParseErrorKind::KeyCollisionKeyAlreadyExists].
+    Error,
+
+    /// Keep the first value seen for the key, silently ignore later ones.
+    FirstWins,
+
+    /// Overwrite the value with the last one seen for the key.
+    LastWins,
+
+    /// If both the existing and the new value are [LSD::List]s, concatenate
+    /// them; otherwise behave like [DuplicateKeyPolicy::LastWins].
+    MergeLists,
+}
+
+impl Default for DuplicateKeyPolicy {
+    fn default() -> Self { DuplicateKeyPolicy::Error }
+}
+
+/// Configurable [LSD] parser, built with [LSD::parser].
+///
+/// Every option defaults to the strict behavior of [LSD::parse]; relax what
+/// you need when parsing untrusted input.
+///
+/// # Examples
+///
+/// ```rust
+/// use lsdata::LSD;
+/// use lsdata::DuplicateKeyPolicy;
+/// use std::io::Cursor;
+///
+/// let lsd = LSD::parser()
+///     .duplicate_keys(DuplicateKeyPolicy::LastWins)
+///     .max_depth(32)
+///     .parse(Cursor::new("a 10\na 20"))
+///     .unwrap();
+/// assert_eq!(lsd.as_level().unwrap().get("a").unwrap().as_value().unwrap(), "20");
+/// ```
+#[derive(Debug, Clone)]
+pub struct LSDParser {
+    duplicate_keys: DuplicateKeyPolicy,
+    comments: bool,
+    max_depth: Option<usize>,
+    allow_trailing_root_content: bool,
+}
+
+impl Default for LSDParser {
+    fn default() -> Self {
+        LSDParser {
+            duplicate_keys: DuplicateKeyPolicy::default(),
+            comments: true,
+            max_depth: None,
+            allow_trailing_root_content: false,
+        }
+    }
+}
+
+impl LSDParser {
+    /// How to resolve a key that already exists in a level. Defaults to
+    /// [DuplicateKeyPolicy::Error].
+    pub fn duplicate_keys(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Whether `#` starts a comment running to the end of the line. Defaults
+    /// to `true`; disable for data that legitimately contains `#`.
+    pub fn comments(mut self, enabled: bool) -> Self {
+        self.comments = enabled;
+        self
+    }
+
+    /// Bound how deeply nested lists/levels may be, so that adversarial
+    /// input like `{{{{{...` cannot blow the stack. Unbounded by default.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Whether content is allowed to follow the root list/level (normally a
+    /// [ParseErrorKind::UnexpectedCharAtFileEnd]). Defaults to `false`.
+    pub fn allow_trailing_root_content(mut self, allowed: bool) -> Self {
+        self.allow_trailing_root_content = allowed;
+        self
+    }
+
+    /// Parse an [LSD] from a [Read] stream using these options.
+    pub fn parse(&self, stream: impl Read) -> Result<LSD, ParseError> {
+        use ParseErrorKind::*;
 
         let mut reader = BufReader::new(stream);
-        let stream = &mut reader
-            .chars()
-            .peekable();
+        let stream = &mut Reader::new(
+            reader
+                .chars(),
+        );
 
-        read_nws(stream)?;
+        read_nws(stream, self)?;
 
-        if let Some(level) = read_level(stream)? {
-            read_nws(stream)?;
+        if let Some(level) = read_level(stream, self, 0)? {
+            read_nws(stream, self)?;
 
-            if let Some(_) = peek(stream)? {
-                return Err(UnexpectedCharAtFileEnd);
+            if !self.allow_trailing_root_content {
+                if peek(stream)?.is_some() {
+                    return Err(stream.error(UnexpectedCharAtFileEnd));
+                }
             }
 
             return Ok(LSD::Level(level));
         };
 
-        if let Some(list) = read_list(stream)? {
-            read_nws(stream)?;
+        if let Some(list) = read_list(stream, self, 0)? {
+            read_nws(stream, self)?;
 
-            if let Some(_) = peek(stream)? {
-                return Err(UnexpectedCharAtFileEnd);
+            if !self.allow_trailing_root_content {
+                if peek(stream)?.is_some() {
+                    return Err(stream.error(UnexpectedCharAtFileEnd));
+                }
             }
 
             return Ok(LSD::List(list));
         };
 
         Ok(LSD::Level(read_level_inner(
-            stream, false,
+            stream, false, self, 0,
         )?))
     }
 }
 
+/// Options for [LSD::parse_with] - a narrower alternative to [LSDParser]
+/// that only exposes the one setting untrusted input needs: a bound on
+/// nesting depth.
+#[derive(Debug, Clone, Copy)]
+pub struct ParseOptions {
+    /// Bound on how deeply nested lists/levels may be. See
+    /// [LSDParser::max_depth].
+    pub max_depth: usize,
+}
+
+impl Default for ParseOptions {
+    /// Defaults to 128, unlike [LSDParser] which is unbounded by default.
+    fn default() -> Self { ParseOptions { max_depth: 128 } }
+}
+
+impl LSD {
+    /// Parse an [LSD] bounded by [ParseOptions], so that adversarial input
+    /// like `[[[[[...` cannot blow the stack of a long-running service that
+    /// accepts untrusted LSD.
+    ///
+    /// Shortcut for `LSD::parser().max_depth(options.max_depth).parse(stream)`.
+    /// [LSD::parse] itself stays unbounded, for compatibility.
+    pub fn parse_with(options: ParseOptions, stream: impl Read) -> Result<LSD, ParseError> {
+        LSDParser::default()
+            .max_depth(options.max_depth)
+            .parse(stream)
+    }
+}
+
+/// Wraps a char stream with a running [Span], so every read/peek knows
+/// where it is in the source.
+///
+/// `X` is a side-channel for variant parsers that need to carry extra state
+/// alongside the stream (e.g. annotations's pending comments) without
+/// forking [peek]/[read]/[read_iws] themselves. It defaults to `()` for the
+/// plain parser here and in [recovery], which need no extra state.
+pub(crate) struct Reader<I: Iterator<Item = io::Result<char>>, X = ()> {
+    inner: Peekable<I>,
+    pub(crate) pos: Span,
+    pub(crate) extra: X,
+}
+
+impl<I: Iterator<Item = io::Result<char>>> Reader<I, ()> {
+    pub(crate) fn new(inner: I) -> Self { Reader::new_with(inner, ()) }
+}
+
+impl<I: Iterator<Item = io::Result<char>>, X> Reader<I, X> {
+    pub(crate) fn new_with(inner: I, extra: X) -> Self {
+        Reader {
+            inner: inner.peekable(),
+            pos: Span::start(),
+            extra,
+        }
+    }
+
+    /// Build a [ParseError] at the current position.
+    pub(crate) fn error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            kind,
+            span: self.pos,
+        }
+    }
+
+    /// Build a [ParseError] at a previously recorded position.
+    pub(crate) fn error_at(&self, kind: ParseErrorKind, pos: Span) -> ParseError {
+        ParseError { kind, span: pos }
+    }
+}
+
 /// Peek a character from the stream.
-fn peek(
-    stream: &mut Peekable<impl Iterator<Item = io::Result<char>>>,
+pub(crate) fn peek<I: Iterator<Item = io::Result<char>>, X>(
+    stream: &mut Reader<I, X>,
 ) -> Result<
     Option<(
         char,
@@ -195,34 +459,59 @@ fn peek(
     )>,
     ParseError,
 > {
-    Ok(match stream.peek() {
-        Some(Err(_)) =>
-            return Err(stream
-                .next()
-                .unwrap()
-                .unwrap_err())?,
-        Some(Ok(ch)) => Some((*ch, || {
-            stream
+    Ok(match stream
+        .inner
+        .peek()
+    {
+        Some(Err(_)) => {
+            let err = stream
+                .inner
                 .next()
                 .unwrap()
-                .unwrap()
-        })),
+                .unwrap_err();
+            return Err(stream.error(ParseErrorKind::ReadFailure(err)));
+        },
+        Some(Ok(ch)) => {
+            let ch = *ch;
+            Some((ch, move || {
+                stream
+                    .inner
+                    .next()
+                    .unwrap()
+                    .unwrap();
+                stream
+                    .pos
+                    .advance(ch);
+                ch
+            }))
+        },
         None => None,
     })
 }
 
 /// Read a character from the stream.
-fn read(
-    stream: &mut Peekable<impl Iterator<Item = io::Result<char>>>,
+pub(crate) fn read<I: Iterator<Item = io::Result<char>>, X>(
+    stream: &mut Reader<I, X>,
 ) -> Result<Option<char>, ParseError> {
-    Ok(stream
+    match stream
+        .inner
         .next()
-        .transpose()?)
+        .transpose()
+    {
+        Ok(Some(ch)) => {
+            stream
+                .pos
+                .advance(ch);
+            Ok(Some(ch))
+        },
+        Ok(None) => Ok(None),
+        Err(err) => Err(stream.error(ParseErrorKind::ReadFailure(err))),
+    }
 }
 
 /// Read a sequence of whitespaces (' ' and '\t') from the stream.
-fn read_iws(
-    stream: &mut Peekable<impl Iterator<Item = io::Result<char>>>,
+pub(crate) fn read_iws<I: Iterator<Item = io::Result<char>>, X>(
+    stream: &mut Reader<I, X>,
 ) -> Result<String, ParseError> {
     let mut result = String::new();
     while let Some((' ' | '\t', accept)) = peek(stream)? {
@@ -231,9 +520,31 @@ fn read_iws(
     Ok(result)
 }
 
-/// Read a sequence of whitespaces with newlines ('\r' and '\n') from the stream.
+/// Decode one hex digit.
+pub(crate) fn u4_from_hex_char(ch: char) -> Result<u8, ()> {
+    match ch {
+        'a'..='f' => Ok(ch as u8 - b'a' + 10),
+        'A'..='F' => Ok(ch as u8 - b'A' + 10),
+        '0'..='9' => Ok(ch as u8 - b'0'),
+        _ => Err(()),
+    }
+}
+
+/// Decode two hex digits into a byte.
+pub(crate) fn u8_from_2_hex_chars(ch1: char, ch2: char) -> Result<u8, ()> {
+    Ok(u4_from_hex_char(ch1)? << 4 | u4_from_hex_char(ch2)?)
+}
+
+/// Decode four hex digits into a UTF-16 code unit (one half of a `\u` escape).
+pub(crate) fn u16_from_4_hex_chars(ch1: char, ch2: char, ch3: char, ch4: char) -> Result<u16, ()> {
+    Ok((u8_from_2_hex_chars(ch1, ch2)? as u16) << 8 | u8_from_2_hex_chars(ch3, ch4)? as u16)
+}
+
+/// Read a sequence of whitespaces with newlines ('\r' and '\n') from the
+/// stream. `#` only starts a comment when `options.comments` is enabled.
 fn read_nws(
-    stream: &mut Peekable<impl Iterator<Item = io::Result<char>>>,
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+    options: &LSDParser,
 ) -> Result<bool, ParseError> {
     read_iws(stream)?;
 
@@ -251,7 +562,7 @@ fn read_nws(
                 accept();
                 continue;
             },
-            Some(('#', accept)) => {
+            Some(('#', accept)) if options.comments => {
                 accept();
                 in_comment = true;
             },
@@ -264,36 +575,41 @@ fn read_nws(
 
 /// Read an LSD from the stream.
 fn read_lsd(
-    stream: &mut Peekable<impl Iterator<Item = io::Result<char>>>,
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
     value_ignore_char: Option<char>,
+    options: &LSDParser,
+    depth: usize,
 ) -> Result<Option<LSD>, ParseError> {
-    if let Some(list) = read_list(stream)? {
+    if let Some(list) = read_list(stream, options, depth)? {
         return Ok(Some(LSD::List(list)));
     }
 
-    if let Some(level) = read_level(stream)? {
+    if let Some(level) = read_level(stream, options, depth)? {
         return Ok(Some(LSD::Level(level)));
     }
 
-    if let Some(value) = read_value(stream, value_ignore_char)? {
+    if let Some(value) = read_value(stream, value_ignore_char, options.comments)? {
         return Ok(Some(LSD::Value(value)));
     }
 
     Ok(None)
 }
 
-/// Read a value from the stream.
-fn read_value(
-    stream: &mut Peekable<impl Iterator<Item = io::Result<char>>>,
+/// Read a value from the stream. Shared by the default parser and annotations -
+/// see [read_word] for why `comments_enabled` is a plain `bool` here rather
+/// than a whole [LSDParser].
+pub(crate) fn read_value<X>(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>, X>,
     ignore_char: Option<char>,
+    comments_enabled: bool,
 ) -> Result<Option<Value>, ParseError> {
-    let Some(mut result) = read_value_part(stream, ignore_char)? else {
+    let Some(mut result) = read_value_part(stream, ignore_char, comments_enabled)? else {
         return Ok(None);
     };
 
     Ok(Some(loop {
         let iws = read_iws(stream)?;
-        match read_value_part(stream, ignore_char)? {
+        match read_value_part(stream, ignore_char, comments_enabled)? {
             Some(part) => {
                 // Rust, why no push_string?
                 result.push_str(&iws);
@@ -305,11 +621,12 @@ fn read_value(
 }
 
 /// Read a value part (word or string) from the stream.
-fn read_value_part(
-    stream: &mut Peekable<impl Iterator<Item = io::Result<char>>>,
+pub(crate) fn read_value_part<X>(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>, X>,
     ignore_char: Option<char>,
+    comments_enabled: bool,
 ) -> Result<Option<String>, ParseError> {
-    if let Some(word) = read_word(stream, ignore_char)? {
+    if let Some(word) = read_word(stream, ignore_char, comments_enabled)? {
         return Ok(Some(word));
     }
 
@@ -321,14 +638,23 @@ fn read_value_part(
 }
 
 /// Read a word (non-whitespace, non-comment, non-string) from the stream.
-fn read_word(
-    stream: &mut Peekable<impl Iterator<Item = io::Result<char>>>,
+/// `#` only ends a word when `comments_enabled` is `true`.
+///
+/// Shared by the default parser (where this is driven by [LSDParser::comments]) and
+/// annotations (which always enables comments) - a plain `bool` rather than
+/// a whole `&LSDParser` keeps this usable from both without coupling it to
+/// the default parser's options type, and generic `X` keeps it usable with
+/// annotations's comment-tracking side channel.
+pub(crate) fn read_word<X>(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>, X>,
     ignore_char: Option<char>,
+    comments_enabled: bool,
 ) -> Result<Option<String>, ParseError> {
     let mut result = String::new();
     loop {
         match peek(stream)? {
-            None | Some((' ' | '\t' | '\r' | '\n' | '\'' | '"' | '#', _)) => break,
+            None | Some((' ' | '\t' | '\r' | '\n' | '\'' | '"', _)) => break,
+            Some(('#', _)) if comments_enabled => break,
             Some((ch, _)) if Some(ch) == ignore_char => break,
             Some((_, accept)) => result.push(accept()),
         }
@@ -340,37 +666,33 @@ fn read_word(
 }
 
 /// Read a string (starting and ending with `'` or `"`) from the stream.
-fn read_string(
-    stream: &mut Peekable<impl Iterator<Item = io::Result<char>>>,
+/// Shared by the default parser and annotations - unlike [recovery], neither
+/// needs to recover from a malformed escape or an early file end, so both
+/// can use this exact grammar as-is.
+pub(crate) fn read_string<X>(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>, X>,
 ) -> Result<Option<String>, ParseError> {
-    use ParseError::*;
+    use ParseErrorKind::*;
+
+    let start_pos = stream.pos;
 
     let closing_char = match peek(stream)? {
         Some(('"', accept)) | Some(('\'', accept)) => accept(),
         _ => return Ok(None),
     };
 
-    fn u4_from_hex_char(ch: char) -> Result<u8, ()> {
-        match ch {
-            'a'..='f' => Ok(ch as u8 - 'a' as u8 + 10),
-            'A'..='F' => Ok(ch as u8 - 'A' as u8 + 10),
-            '0'..='9' => Ok(ch as u8 - '0' as u8),
-            _ => Err(()),
-        }
-    }
-
-    fn u8_from_2_hex_chars(ch1: char, ch2: char) -> Result<u8, ()> {
-        Ok(u4_from_hex_char(ch1)? << 4 | u4_from_hex_char(ch2)?)
-    }
-
-    fn u16_from_4_hex_chars(ch1: char, ch2: char, ch3: char, ch4: char) -> Result<u16, ()> {
-        Ok((u8_from_2_hex_chars(ch1, ch2)? as u16) << 8 | u8_from_2_hex_chars(ch3, ch4)? as u16)
+    // reads up to EOF always report the start of the string/value, not the
+    // file end, since that is the position a user actually needs to look at
+    macro_rules! read_or_string_end {
+        ($stream:expr) => {
+            read($stream)?.ok_or_else(|| $stream.error_at(UnexpectedStringEnd, start_pos))?
+        };
     }
 
     let mut result = String::new();
     loop {
-        match read(stream)?.ok_or(UnexpectedStringEnd)? {
-            '\\' => match read(stream)?.ok_or(UnexpectedCharEscapeEnd)? {
+        match read_or_string_end!(stream) {
+            '\\' => match read(stream)?.ok_or_else(|| stream.error(UnexpectedCharEscapeEnd))? {
                 '"' => result.push('"'),
                 '\\' => result.push('\\'),
                 '\'' => result.push('\''),
@@ -384,45 +706,46 @@ fn read_string(
                 'r' | 'R' => result.push('\r'),
                 'x' | 'X' => {
                     let first_byte = u8_from_2_hex_chars(
-                        read(stream)?.ok_or(UnexpectedStringEnd)?,
-                        read(stream)?.ok_or(UnexpectedStringEnd)?,
+                        read_or_string_end!(stream),
+                        read_or_string_end!(stream),
                     )
-                    .map_err(|()| UnexpectedCharInByteEscape)?;
+                    .map_err(|()| stream.error(UnexpectedCharInByteEscape))?;
 
                     let mut bytes = vec![first_byte];
 
                     // does not guarantee only 4, can do 8, does not check for 10...
                     for _ in 0..first_byte.leading_ones() {
                         match (
-                            read(stream)?.ok_or(UnexpectedStringEnd)?,
-                            read(stream)?.ok_or(UnexpectedStringEnd)?,
+                            read_or_string_end!(stream),
+                            read_or_string_end!(stream),
                         ) {
                             ('\\', 'x' | 'X') => {},
-                            _ => return Err(UnexpectedCharInByteEscape)?,
+                            _ => return Err(stream.error(UnexpectedCharInByteEscape)),
                         }
 
                         bytes.push(
                             u8_from_2_hex_chars(
-                                read(stream)?.ok_or(UnexpectedStringEnd)?,
-                                read(stream)?.ok_or(UnexpectedStringEnd)?,
+                                read_or_string_end!(stream),
+                                read_or_string_end!(stream),
                             )
-                            .map_err(|()| UnexpectedCharInByteEscape)?,
+                            .map_err(|()| stream.error(UnexpectedCharInByteEscape))?,
                         )
                     }
 
                     result.push_str(
-                        &String::from_utf8(bytes).map_err(|_| UnexpectedCharInByteEscape)?,
+                        &String::from_utf8(bytes)
+                            .map_err(|_| stream.error(UnexpectedCharInByteEscape))?,
                     )
                 },
                 'u' | 'U' => {
                     // read first possibly-surrogate HHHH escape
                     let first_surrogate = u16_from_4_hex_chars(
-                        read(stream)?.ok_or(UnexpectedStringEnd)?,
-                        read(stream)?.ok_or(UnexpectedStringEnd)?,
-                        read(stream)?.ok_or(UnexpectedStringEnd)?,
-                        read(stream)?.ok_or(UnexpectedStringEnd)?,
+                        read_or_string_end!(stream),
+                        read_or_string_end!(stream),
+                        read_or_string_end!(stream),
+                        read_or_string_end!(stream),
                     )
-                    .map_err(|()| UnexpectedCharInUnicodeEscape)?;
+                    .map_err(|()| stream.error(UnexpectedCharInUnicodeEscape))?;
 
                     // try checking if first surrogate is enough
                     let unicode_attempt = char::decode_utf16([first_surrogate])
@@ -435,29 +758,29 @@ fn read_string(
                     // not enough - read second \uHHHH escape and try to parse as pair
 
                     match (
-                        read(stream)?.ok_or(UnexpectedStringEnd)?,
-                        read(stream)?.ok_or(UnexpectedStringEnd)?,
+                        read_or_string_end!(stream),
+                        read_or_string_end!(stream),
                     ) {
                         ('\\', 'u' | 'U') => {},
-                        _ => return Err(UnexpectedCharInUnicodeEscape)?,
+                        _ => return Err(stream.error(UnexpectedCharInUnicodeEscape)),
                     }
 
                     let second_surrogate = u16_from_4_hex_chars(
-                        read(stream)?.ok_or(UnexpectedStringEnd)?,
-                        read(stream)?.ok_or(UnexpectedStringEnd)?,
-                        read(stream)?.ok_or(UnexpectedStringEnd)?,
-                        read(stream)?.ok_or(UnexpectedStringEnd)?,
+                        read_or_string_end!(stream),
+                        read_or_string_end!(stream),
+                        read_or_string_end!(stream),
+                        read_or_string_end!(stream),
                     )
-                    .map_err(|()| UnexpectedCharInUnicodeEscape)?;
+                    .map_err(|()| stream.error(UnexpectedCharInUnicodeEscape))?;
 
                     result.push(
                         char::decode_utf16([first_surrogate, second_surrogate])
                             .next()
                             .unwrap()
-                            .map_err(|_| UnexpectedCharInUnicodeEscape)?,
+                            .map_err(|_| stream.error(UnexpectedCharInUnicodeEscape))?,
                     );
                 },
-                _ => return Err(UnexpectedCharEscapeEnd)?,
+                _ => return Err(stream.error(UnexpectedCharEscapeEnd)),
             },
             ch if ch == closing_char => return Ok(Some(result)),
             ch => result.push(ch),
@@ -467,26 +790,39 @@ fn read_string(
 
 /// Read a level (`{}`) from the stream.
 fn read_level(
-    stream: &mut Peekable<impl Iterator<Item = io::Result<char>>>,
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+    options: &LSDParser,
+    depth: usize,
 ) -> Result<Option<Level>, ParseError> {
+    use ParseErrorKind::*;
+
     match peek(stream)? {
         Some(('{', accept)) => accept(),
         _ => return Ok(None),
     };
 
-    read_nws(stream)?;
+    if options.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+        return Err(stream.error(ExceededMaxDepth));
+    }
+
+    read_nws(stream, options)?;
 
     Ok(Some(read_level_inner(
-        stream, true,
+        stream,
+        true,
+        options,
+        depth + 1,
     )?))
 }
 
 /// Read a sequence of key-LSD pairs from the stream.
 fn read_level_inner(
-    stream: &mut Peekable<impl Iterator<Item = io::Result<char>>>,
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
     level_ends_with_close: bool,
+    options: &LSDParser,
+    depth: usize,
 ) -> Result<Level, ParseError> {
-    use ParseError::*;
+    use ParseErrorKind::*;
 
     let mut results = Level::default();
     Ok(loop {
@@ -497,38 +833,66 @@ fn read_level_inner(
             }
         }
 
-        let key = match read_key_path(stream)? {
+        let key_pos = stream.pos;
+        let key = match read_key_path(stream, options.comments)? {
             Some(key) => key,
-            None if level_ends_with_close => return Err(ExpectedKeyOrEnd),
+            None if level_ends_with_close => return Err(stream.error(ExpectedKeyOrEnd)),
             None => return Ok(results),
         };
 
-        read_nws(stream)?;
+        read_nws(stream, options)?;
 
-        let lsd = read_lsd(stream, Some('}'))?.ok_or(ExpectedLSDAfterKey)?;
+        let lsd = read_lsd(stream, Some('}'), options, depth)?
+            .ok_or_else(|| stream.error(ExpectedLSDAfterKey))?;
 
-        read_nws(stream)?;
+        read_nws(stream, options)?;
 
-        fn merge_level(insert_into: &mut Level, level: Level) -> Result<(), ParseError> {
+        fn merge_level(
+            insert_into: &mut Level,
+            level: Level,
+            err_pos: Span,
+            duplicate_keys: DuplicateKeyPolicy,
+        ) -> Result<(), ParseError> {
             for (key, value) in level.into_iter() {
-                match value {
-                    LSD::Value(value) => insert_into
-                        .insert(key.clone(), LSD::Value(value))
-                        .is_none()
-                        .then_some(())
-                        .ok_or_else(|| KeyCollisionKeyAlreadyExists(key))?,
-                    LSD::List(list) => insert_into
-                        .insert(key.clone(), LSD::List(list))
-                        .is_none()
-                        .then_some(())
-                        .ok_or_else(|| KeyCollisionKeyAlreadyExists(key))?,
-                    LSD::Level(lvl) => match insert_into
+                if matches!(value, LSD::Level(_)) {
+                    let LSD::Level(lvl) = value else {
+                        unreachable!()
+                    };
+                    match insert_into
                         .entry(key)
                         .or_insert_with(|| LSD::Level(Level::default()))
                     {
-                        LSD::Value(_) => return Err(KeyCollisionShouldBeLevelButIsNot)?,
-                        LSD::List(_) => return Err(KeyCollisionShouldBeLevelButIsNot)?,
-                        LSD::Level(ref mut insert_into) => merge_level(insert_into, lvl)?,
+                        LSD::Value(_) => return Err(ParseError {
+                            kind: KeyCollisionShouldBeLevelButIsNot,
+                            span: err_pos,
+                        }),
+                        LSD::List(_) => return Err(ParseError {
+                            kind: KeyCollisionShouldBeLevelButIsNot,
+                            span: err_pos,
+                        }),
+                        LSD::Level(ref mut insert_into) =>
+                            merge_level(insert_into, lvl, err_pos, duplicate_keys)?,
+                    }
+                    continue;
+                }
+
+                match insert_into.entry(key.clone()) {
+                    indexmap::map::Entry::Vacant(entry) => {
+                        entry.insert(value);
+                    },
+                    indexmap::map::Entry::Occupied(mut entry) => match duplicate_keys {
+                        DuplicateKeyPolicy::Error => return Err(ParseError {
+                            kind: KeyCollisionKeyAlreadyExists(key),
+                            span: err_pos,
+                        }),
+                        DuplicateKeyPolicy::FirstWins => {},
+                        DuplicateKeyPolicy::LastWins => {
+                            entry.insert(value);
+                        },
+                        DuplicateKeyPolicy::MergeLists => match (entry.get_mut(), value) {
+                            (LSD::List(existing), LSD::List(new)) => existing.extend(new),
+                            (existing, value) => *existing = value,
+                        },
                     },
                 }
             }
@@ -561,38 +925,49 @@ fn read_level_inner(
             }
         }
 
-        merge_level(&mut results, result)?;
+        merge_level(&mut results, result, key_pos, options.duplicate_keys)?;
     })
 }
 
-/// Read a key word (word, but also not level or list) from the stream.
-fn read_key_word(
-    stream: &mut Peekable<impl Iterator<Item = io::Result<char>>>,
+/// Read a key word (word, but also not level or list) from the stream. `#`
+/// only ends a key word when `comments_enabled` is `true`. Shared by
+/// the default parser and annotations - see [read_word] for why this takes a
+/// plain `bool` and a generic `X`.
+pub(crate) fn read_key_word<X>(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>, X>,
+    comments_enabled: bool,
 ) -> Result<Option<String>, ParseError> {
     let mut result = String::new();
     Ok(loop {
         match peek(stream)? {
             None
             | Some((
-                ' ' | '\t' | '\r' | '\n' | '\'' | '"' | '#' | '{' | '}' | '[' | ']' | '.',
+                ' ' | '\t' | '\r' | '\n' | '\'' | '"' | '{' | '}' | '[' | ']' | '.',
                 _,
             )) =>
                 break result
                     .is_empty()
                     .not()
                     .then_some(result),
+            Some(('#', _)) if comments_enabled =>
+                break result
+                    .is_empty()
+                    .not()
+                    .then_some(result),
             Some((_, accept)) => result.push(accept()),
         }
     })
 }
 
-/// Read a key part from the stream.
-fn read_key_part(
-    stream: &mut Peekable<impl Iterator<Item = io::Result<char>>>,
+/// Read a key part from the stream. Shared by the default parser and
+/// annotations.
+pub(crate) fn read_key_part<X>(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>, X>,
+    comments_enabled: bool,
 ) -> Result<Option<String>, ParseError> {
     let mut result = String::new();
     loop {
-        if let Some(word) = read_key_word(stream)? {
+        if let Some(word) = read_key_word(stream, comments_enabled)? {
             result.push_str(&word);
             continue;
         }
@@ -609,13 +984,15 @@ fn read_key_part(
     }
 }
 
-/// Read a key path (separated by `.`) from the stream.
-fn read_key_path(
-    stream: &mut Peekable<impl Iterator<Item = io::Result<char>>>,
+/// Read a key path (separated by `.`) from the stream. Shared by
+/// the default parser and annotations.
+pub(crate) fn read_key_path<X>(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>, X>,
+    comments_enabled: bool,
 ) -> Result<Option<Vec<String>>, ParseError> {
-    use ParseError::*;
+    use ParseErrorKind::*;
 
-    let mut result = vec![match read_key_part(stream)? {
+    let mut result = vec![match read_key_part(stream, comments_enabled)? {
         Some(key_part) => key_part,
         None => return Ok(None),
     }];
@@ -626,7 +1003,10 @@ fn read_key_path(
         };
         accept();
 
-        result.push(read_key_part(stream)?.ok_or(ExpectedKeyPartAfterKeySeparator)?);
+        result.push(
+            read_key_part(stream, comments_enabled)?
+                .ok_or_else(|| stream.error(ExpectedKeyPartAfterKeySeparator))?,
+        );
     }
 
     Ok(Some(result))
@@ -634,34 +1014,38 @@ fn read_key_path(
 
 /// Read a list item from the stream.
 fn read_list_lsd(
-    stream: &mut Peekable<impl Iterator<Item = io::Result<char>>>,
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+    options: &LSDParser,
+    depth: usize,
 ) -> Result<Option<LSD>, ParseError> {
-    if let Some(list) = read_list(stream)? {
+    if let Some(list) = read_list(stream, options, depth)? {
         return Ok(Some(LSD::List(list)));
     }
 
-    if let Some(level) = read_level(stream)? {
+    if let Some(level) = read_level(stream, options, depth)? {
         return Ok(Some(LSD::Level(level)));
     }
 
-    if let Some(value) = read_list_value(stream)? {
+    if let Some(value) = read_list_value(stream, options.comments)? {
         return Ok(Some(LSD::Value(value)));
     }
 
     Ok(None)
 }
 
-/// Read a list value (same as regular value, but may not contain level or list) from the stream.
-fn read_list_value(
-    stream: &mut Peekable<impl Iterator<Item = io::Result<char>>>,
+/// Read a list value (same as regular value, but may not contain level or
+/// list) from the stream. Shared by the default parser and annotations.
+pub(crate) fn read_list_value<X>(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>, X>,
+    comments_enabled: bool,
 ) -> Result<Option<Value>, ParseError> {
-    let Some(mut result) = read_key_part(stream)? else {
+    let Some(mut result) = read_key_part(stream, comments_enabled)? else {
         return Ok(None);
     };
 
     Ok(Some(loop {
         let iws = read_iws(stream)?;
-        match read_key_part(stream)? {
+        match read_key_part(stream, comments_enabled)? {
             Some(part) => {
                 // Rust, why no push_string?
                 result.push_str(&iws);
@@ -674,16 +1058,22 @@ fn read_list_value(
 
 /// Read a list (`[]`) from the stream.
 fn read_list(
-    stream: &mut Peekable<impl Iterator<Item = io::Result<char>>>,
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+    options: &LSDParser,
+    depth: usize,
 ) -> Result<Option<List>, ParseError> {
-    use ParseError::*;
+    use ParseErrorKind::*;
 
     match peek(stream)? {
         Some(('[', accept)) => accept(),
         _ => return Ok(None),
     };
 
-    read_nws(stream)?;
+    if options.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+        return Err(stream.error(ExceededMaxDepth));
+    }
+
+    read_nws(stream, options)?;
 
     let mut results = List::default();
     Ok(Some(loop {
@@ -695,12 +1085,154 @@ fn read_list(
             _ => {},
         }
 
-        results.push(read_list_lsd(stream)?.ok_or(ExpectedListLSDOrEnd)?);
+        results.push(
+            read_list_lsd(stream, options, depth + 1)?
+                .ok_or_else(|| stream.error(ExpectedListLSDOrEnd))?,
+        );
 
-        read_nws(stream)?;
+        read_nws(stream, options)?;
     }))
 }
 
+//
+// Write
+//
+
+/// Characters that force a bare word to be quoted, both for values and for
+/// key parts. Mirrors the set of characters [read_word] and [read_key_word]
+/// stop at.
+const RESERVED_CHARS: [char; 12] = [
+    ' ', '\t', '\r', '\n', '\'', '"', '#', '{', '}', '[', ']', '.',
+];
+
+fn needs_quoting(part: &str) -> bool {
+    part.is_empty()
+        || part
+            .chars()
+            .any(|ch| RESERVED_CHARS.contains(&ch))
+}
+
+fn write_quoted(part: &str, w: &mut impl io::Write) -> io::Result<()> {
+    write!(w, "\"")?;
+    for ch in part.chars() {
+        match ch {
+            '"' => write!(w, "\\\"")?,
+            '\\' => write!(w, "\\\\")?,
+            '\n' => write!(w, "\\n")?,
+            '\t' => write!(w, "\\t")?,
+            '\r' => write!(w, "\\r")?,
+            ch if (ch as u32) < 0x20 || ch as u32 == 0x7f => write!(w, "\\x{:02X}", ch as u32)?,
+            ch if ch.is_control() => write!(w, "\\u{:04X}", ch as u32)?,
+            ch => write!(w, "{ch}")?,
+        }
+    }
+    write!(w, "\"")
+}
+
+/// Write a bare word if possible, otherwise fall back to a quoted string.
+/// Used for both values and key parts, since they share the same reserved
+/// character set.
+fn write_part(part: &str, w: &mut impl io::Write) -> io::Result<()> {
+    if needs_quoting(part) {
+        write_quoted(part, w)
+    } else {
+        write!(w, "{part}")
+    }
+}
+
+fn write_indent(w: &mut impl io::Write, indent: usize, depth: usize) -> io::Result<()> {
+    write!(w, "{:1$}", "", indent * depth)
+}
+
+/// Write `key value` lines for every entry of a level, without the
+/// surrounding `{ }` (used both for the file root and for the inside of a
+/// nested level).
+fn write_level_body(
+    level: &Level,
+    w: &mut impl io::Write,
+    indent: usize,
+    depth: usize,
+) -> io::Result<()> {
+    for (i, (key, value)) in level
+        .iter()
+        .enumerate()
+    {
+        if i > 0 {
+            writeln!(w)?;
+        }
+        write_indent(w, indent, depth)?;
+        write_part(key, w)?;
+        write!(w, " ")?;
+        write_lsd(value, w, indent, depth)?;
+    }
+    Ok(())
+}
+
+/// Write an [LSD] in whatever position it appears (value of a key, or item
+/// of a list), at the given indentation depth.
+fn write_lsd(lsd: &LSD, w: &mut impl io::Write, indent: usize, depth: usize) -> io::Result<()> {
+    match lsd {
+        LSD::Value(value) => write_part(value, w),
+        LSD::Level(level) if level.is_empty() => write!(w, "{{}}"),
+        LSD::Level(level) => {
+            writeln!(w, "{{")?;
+            write_level_body(level, w, indent, depth + 1)?;
+            writeln!(w)?;
+            write_indent(w, indent, depth)?;
+            write!(w, "}}")
+        },
+        LSD::List(list) if list.is_empty() => write!(w, "[]"),
+        LSD::List(list) => {
+            writeln!(w, "[")?;
+            for (i, item) in list
+                .iter()
+                .enumerate()
+            {
+                if i > 0 {
+                    writeln!(w)?;
+                }
+                write_indent(w, indent, depth + 1)?;
+                write_lsd(item, w, indent, depth + 1)?;
+            }
+            writeln!(w)?;
+            write_indent(w, indent, depth)?;
+            write!(w, "]")
+        },
+    }
+}
+
+impl LSD {
+    /// Write this [LSD] back out as valid LSD text.
+    ///
+    /// A [Level] at the root is written without surrounding `{ }` (mirroring
+    /// how [LSD::parse] accepts a brace-less root), matching the invariant
+    /// `LSD::parse(lsd.to_string()) == lsd`.
+    pub fn write(&self, w: &mut impl io::Write) -> io::Result<()> {
+        match self {
+            LSD::Level(level) => write_level_body(level, w, 4, 0),
+            lsd => write_lsd(lsd, w, 4, 0),
+        }
+    }
+
+    /// Same as [LSD::write], but using a custom indent width and returning a
+    /// [String] instead of writing to an [io::Write].
+    pub fn to_string_pretty(&self, indent: usize) -> String {
+        let mut buf = Vec::new();
+        match self {
+            LSD::Level(level) => write_level_body(level, &mut buf, indent, 0),
+            lsd => write_lsd(lsd, &mut buf, indent, 0),
+        }
+        .expect("writing LSD to a Vec<u8> never fails");
+        String::from_utf8(buf).expect("LSD writer only ever emits valid UTF-8")
+    }
+}
+
+impl Display for LSD {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_string_pretty(4))
+    }
+}
+
 //
 // KeyPath
 //
@@ -754,6 +1286,10 @@ impl From<usize> for KeyPathPart {
 
 pub type KeyPath = [KeyPathPart];
 
+/// Owned version of [KeyPath], as produced by [LSD::parse_with_annotations]'s
+/// comment table.
+pub type KeyPathBuf = Vec<KeyPathPart>;
+
 /// Macro for creating key paths.
 ///
 /// You may use `.`, `,` and `;` as separators, as well as spaces.
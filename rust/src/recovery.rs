@@ -0,0 +1,771 @@
+//! Error-recovering parse mode that collects every problem instead of
+//! stopping at the first one, for tools (linters, formatters) that want to
+//! report as many issues as possible in a single pass.
+//!
+//! This borrows the accumulate-and-continue model compilers use for lexing:
+//! a malformed entry is replaced with a placeholder, the reader
+//! resynchronizes at the next key/list-item/closing-brace boundary, and
+//! parsing keeps going. [LSD::parse] itself is untouched and still stops at
+//! the first error.
+//!
+//! This reuses the crate root's [Reader]/[peek]/[read]/[read_iws] and hex
+//! escape helpers - only the grammar functions that actually differ (taking
+//! an `errors: &mut Vec<ParseError>` to push into and resync instead of
+//! bailing out) are duplicated from `lib.rs`.
+
+use std::io;
+use std::io::BufReader;
+use std::io::Read;
+use std::ops::Not;
+
+use utf8_chars::BufReadCharsExt;
+
+use crate::peek;
+use crate::read;
+use crate::read_iws;
+use crate::u16_from_4_hex_chars;
+use crate::u8_from_2_hex_chars;
+use crate::DuplicateKeyPolicy;
+use crate::Level;
+use crate::List;
+use crate::ParseError;
+use crate::ParseErrorKind;
+use crate::Reader;
+use crate::Span;
+use crate::Value;
+use crate::LSD;
+
+impl LSD {
+    /// Parse an [LSD], recovering from errors instead of stopping at the
+    /// first one.
+    ///
+    /// A key with no value after it gets a placeholder (an empty
+    /// [LSD::Value]), a string with a malformed escape or no closing quote is
+    /// cut short at what was read so far, and inside a `{}` level or `[]`
+    /// list the parser resynchronizes at the next line or closing `}`/`]` -
+    /// the same boundaries [LSD::parse] already recognizes - so one bad line
+    /// does not prevent the rest of that level or list from being checked.
+    /// At the brace-less file root there is no such resync: a character that
+    /// cannot start a key ends the parse there, is reported as
+    /// [ParseErrorKind::UnexpectedCharAtFileEnd], and nothing after it is
+    /// read. Returns the best-effort tree alongside
+    /// every [ParseError] encountered along the way, in the order they were
+    /// found.
+    ///
+    /// `duplicate_keys` resolves a key that already exists in a level, same
+    /// as [crate::LSDParser::duplicate_keys] - with
+    /// [DuplicateKeyPolicy::Error], a collision is reported the same way any
+    /// other recoverable problem is, and parsing continues.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lsdata::DuplicateKeyPolicy;
+    /// use lsdata::LSD;
+    /// use std::io::Cursor;
+    ///
+    /// let (lsd, errors) = LSD::parse_recovering(
+    ///     Cursor::new("a 10\nb {\nc 20"),
+    ///     DuplicateKeyPolicy::Error,
+    /// );
+    /// assert!(!errors.is_empty());
+    /// assert_eq!(
+    ///     lsd.as_level().unwrap().get("a").unwrap().as_value().unwrap(),
+    ///     "10",
+    /// );
+    /// ```
+    pub fn parse_recovering(
+        stream: impl Read,
+        duplicate_keys: DuplicateKeyPolicy,
+    ) -> (LSD, Vec<ParseError>) {
+        use ParseErrorKind::*;
+
+        let mut reader = BufReader::new(stream);
+        let stream = &mut Reader::new(
+            reader
+                .chars(),
+        );
+        let mut errors = Vec::new();
+
+        match read_root(stream, duplicate_keys, &mut errors) {
+            Ok(lsd) => (lsd, errors),
+            Err(err) => {
+                // a raw I/O failure (or exceeding MAX_DEPTH) is not something
+                // resync can route around - report it and hand back an empty
+                // tree
+                errors.push(err);
+                (LSD::default(), errors)
+            },
+        }
+    }
+}
+
+/// Bound on how deeply nested lists/levels may be, so that adversarial input
+/// like `"[".repeat(200_000)` cannot blow the stack. [LSD::parse_recovering]
+/// takes no options, so unlike [crate::LSDParser::max_depth] this isn't
+/// configurable - it mirrors [crate::ParseOptions::default]'s bound instead.
+const MAX_DEPTH: usize = 128;
+
+fn read_root(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+    duplicate_keys: DuplicateKeyPolicy,
+    errors: &mut Vec<ParseError>,
+) -> Result<LSD, ParseError> {
+    use ParseErrorKind::*;
+
+    read_nws(stream)?;
+
+    let lsd = if let Some(level) = read_level(stream, 0, duplicate_keys, errors)? {
+        LSD::Level(level)
+    } else if let Some(list) = read_list(stream, 0, duplicate_keys, errors)? {
+        LSD::List(list)
+    } else {
+        LSD::Level(read_level_inner(stream, false, 0, duplicate_keys, errors)?)
+    };
+
+    read_nws(stream)?;
+
+    if peek(stream)?.is_some() {
+        errors.push(stream.error(UnexpectedCharAtFileEnd));
+    }
+
+    Ok(lsd)
+}
+
+/// Same grammar as the default parser's `read_nws` - comments are always
+/// recognized (there is no [crate::LSDParser] option to thread through a
+/// best-effort pass).
+fn read_nws(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+) -> Result<bool, ParseError> {
+    read_iws(stream)?;
+
+    let mut has_newline = false;
+    let mut in_comment = false;
+    loop {
+        match peek(stream)? {
+            Some(('\r' | '\n', accept)) => {
+                accept();
+                in_comment = false;
+                has_newline = true;
+            },
+            Some((_, accept)) if in_comment => {
+                accept();
+                continue;
+            },
+            Some(('#', accept)) => {
+                accept();
+                in_comment = true;
+            },
+            _ => return Ok(has_newline),
+        };
+
+        read_iws(stream)?;
+    }
+}
+
+/// Skip forward until `stream` is positioned right before one of `stop`, or
+/// at the end of the file. Always makes progress: every character that
+/// isn't a stop character gets consumed.
+fn skip_to_resync(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+    stop: &[char],
+) -> Result<(), ParseError> {
+    loop {
+        match peek(stream)? {
+            None => return Ok(()),
+            Some((ch, _)) if stop.contains(&ch) => return Ok(()),
+            Some((_, accept)) => {
+                accept();
+            },
+        }
+    }
+}
+
+/// Read an LSD from the stream, for use as the value of a key.
+fn read_lsd(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+    value_ignore_char: Option<char>,
+    depth: usize,
+    duplicate_keys: DuplicateKeyPolicy,
+    errors: &mut Vec<ParseError>,
+) -> Result<Option<LSD>, ParseError> {
+    if let Some(list) = read_list(stream, depth, duplicate_keys, errors)? {
+        return Ok(Some(LSD::List(list)));
+    }
+
+    if let Some(level) = read_level(stream, depth, duplicate_keys, errors)? {
+        return Ok(Some(LSD::Level(level)));
+    }
+
+    if let Some(value) = read_value(stream, value_ignore_char, errors)? {
+        return Ok(Some(LSD::Value(value)));
+    }
+
+    Ok(None)
+}
+
+fn read_value(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+    ignore_char: Option<char>,
+    errors: &mut Vec<ParseError>,
+) -> Result<Option<Value>, ParseError> {
+    let Some(mut result) = read_value_part(stream, ignore_char, errors)? else {
+        return Ok(None);
+    };
+
+    Ok(Some(loop {
+        let iws = read_iws(stream)?;
+        match read_value_part(stream, ignore_char, errors)? {
+            Some(part) => {
+                result.push_str(&iws);
+                result.push_str(&part);
+            },
+            None => break result,
+        }
+    }))
+}
+
+fn read_value_part(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+    ignore_char: Option<char>,
+    errors: &mut Vec<ParseError>,
+) -> Result<Option<String>, ParseError> {
+    if let Some(word) = read_word(stream, ignore_char)? {
+        return Ok(Some(word));
+    }
+
+    if let Some(string) = read_string(stream, errors)? {
+        return Ok(Some(string));
+    }
+
+    Ok(None)
+}
+
+fn read_word(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+    ignore_char: Option<char>,
+) -> Result<Option<String>, ParseError> {
+    let mut result = String::new();
+    loop {
+        match peek(stream)? {
+            None | Some((' ' | '\t' | '\r' | '\n' | '\'' | '"' | '#', _)) => break,
+            Some((ch, _)) if Some(ch) == ignore_char => break,
+            Some((_, accept)) => result.push(accept()),
+        }
+    }
+    Ok(result
+        .is_empty()
+        .not()
+        .then_some(result))
+}
+
+/// Read a string, same grammar as [crate::LSD::parse]'s, except a malformed
+/// escape or a file end mid-string is not fatal: it is recorded in `errors`
+/// and the string is cut short with whatever content was read so far.
+fn read_string(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+    errors: &mut Vec<ParseError>,
+) -> Result<Option<String>, ParseError> {
+    use ParseErrorKind::*;
+
+    let start_pos = stream.pos;
+
+    let closing_char = match peek(stream)? {
+        Some(('"', accept)) | Some(('\'', accept)) => accept(),
+        _ => return Ok(None),
+    };
+
+    let mut result = String::new();
+
+    // every failure point below cuts the string short at `result` instead of
+    // aborting the whole parse - this is the one place recovery collapses
+    // several distinct error kinds into "stop reading this string"; like
+    // `LSD::parse`, a file end mid-string is reported at the string's start
+    macro_rules! read_or_recover {
+        ($stream:expr, $kind:expr) => {
+            match read($stream)? {
+                Some(ch) => ch,
+                None => {
+                    let span = if matches!($kind, UnexpectedStringEnd) {
+                        start_pos
+                    } else {
+                        $stream.pos
+                    };
+                    errors.push($stream.error_at($kind, span));
+                    return Ok(Some(result));
+                },
+            }
+        };
+    }
+
+    loop {
+        match read_or_recover!(stream, UnexpectedStringEnd) {
+            '\\' => match read_or_recover!(stream, UnexpectedCharEscapeEnd) {
+                '"' => result.push('"'),
+                '\\' => result.push('\\'),
+                '\'' => result.push('\''),
+                '0' => result.push('\0'),
+                'a' | 'A' => result.push('\x07'),
+                'b' | 'B' => result.push('\x08'),
+                't' | 'T' => result.push('\t'),
+                'n' | 'N' => result.push('\n'),
+                'v' | 'V' => result.push('\x0b'),
+                'f' | 'F' => result.push('\x0c'),
+                'r' | 'R' => result.push('\r'),
+                'x' | 'X' => {
+                    let first_byte = match u8_from_2_hex_chars(
+                        read_or_recover!(stream, UnexpectedStringEnd),
+                        read_or_recover!(stream, UnexpectedStringEnd),
+                    ) {
+                        Ok(byte) => byte,
+                        Err(()) => {
+                            errors.push(stream.error(UnexpectedCharInByteEscape));
+                            return Ok(Some(result));
+                        },
+                    };
+
+                    let mut bytes = vec![first_byte];
+
+                    let mut failed = false;
+                    for _ in 0..first_byte.leading_ones() {
+                        match (
+                            read_or_recover!(stream, UnexpectedStringEnd),
+                            read_or_recover!(stream, UnexpectedStringEnd),
+                        ) {
+                            ('\\', 'x' | 'X') => {},
+                            _ => {
+                                failed = true;
+                                break;
+                            },
+                        }
+
+                        match u8_from_2_hex_chars(
+                            read_or_recover!(stream, UnexpectedStringEnd),
+                            read_or_recover!(stream, UnexpectedStringEnd),
+                        ) {
+                            Ok(byte) => bytes.push(byte),
+                            Err(()) => {
+                                failed = true;
+                                break;
+                            },
+                        }
+                    }
+
+                    match String::from_utf8(bytes) {
+                        Ok(decoded) if !failed => result.push_str(&decoded),
+                        _ => {
+                            errors.push(stream.error(UnexpectedCharInByteEscape));
+                            return Ok(Some(result));
+                        },
+                    }
+                },
+                'u' | 'U' => {
+                    match read_unicode_escape(stream, errors, &mut result, start_pos)? {
+                        Ok(()) => {},
+                        Err(()) => return Ok(Some(result)),
+                    }
+                },
+                _ => {
+                    errors.push(stream.error(UnexpectedCharEscapeEnd));
+                    return Ok(Some(result));
+                },
+            },
+            ch if ch == closing_char => return Ok(Some(result)),
+            ch => result.push(ch),
+        }
+    }
+
+    /// Reads a `\uXXXX` escape (and, if it is a high surrogate, the
+    /// following `\uYYYY` low surrogate it must pair with) into `result`.
+    /// `Err(())` means a [ParseError] was already pushed to `errors` and the
+    /// caller should stop reading the string.
+    fn read_unicode_escape(
+        stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+        errors: &mut Vec<ParseError>,
+        result: &mut String,
+        start_pos: Span,
+    ) -> Result<Result<(), ()>, ParseError> {
+        use ParseErrorKind::*;
+
+        macro_rules! hex_or_recover {
+            () => {
+                match read(stream)? {
+                    Some(ch) => ch,
+                    None => {
+                        errors.push(stream.error_at(UnexpectedStringEnd, start_pos));
+                        return Ok(Err(()));
+                    },
+                }
+            };
+        }
+
+        let Ok(first_surrogate) = u16_from_4_hex_chars(
+            hex_or_recover!(),
+            hex_or_recover!(),
+            hex_or_recover!(),
+            hex_or_recover!(),
+        ) else {
+            errors.push(stream.error(UnexpectedCharInUnicodeEscape));
+            return Ok(Err(()));
+        };
+
+        if let Ok(ch) = char::decode_utf16([first_surrogate])
+            .next()
+            .unwrap()
+        {
+            result.push(ch);
+            return Ok(Ok(()));
+        }
+
+        match (
+            hex_or_recover!(),
+            hex_or_recover!(),
+        ) {
+            ('\\', 'u' | 'U') => {},
+            _ => {
+                errors.push(stream.error(UnexpectedCharInUnicodeEscape));
+                return Ok(Err(()));
+            },
+        }
+
+        let Ok(second_surrogate) = u16_from_4_hex_chars(
+            hex_or_recover!(),
+            hex_or_recover!(),
+            hex_or_recover!(),
+            hex_or_recover!(),
+        ) else {
+            errors.push(stream.error(UnexpectedCharInUnicodeEscape));
+            return Ok(Err(()));
+        };
+
+        match char::decode_utf16([first_surrogate, second_surrogate])
+            .next()
+            .unwrap()
+        {
+            Ok(ch) => {
+                result.push(ch);
+                Ok(Ok(()))
+            },
+            Err(_) => {
+                errors.push(stream.error(UnexpectedCharInUnicodeEscape));
+                Ok(Err(()))
+            },
+        }
+    }
+}
+
+/// Read a level (`{}`) from the stream.
+fn read_level(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+    depth: usize,
+    duplicate_keys: DuplicateKeyPolicy,
+    errors: &mut Vec<ParseError>,
+) -> Result<Option<Level>, ParseError> {
+    use ParseErrorKind::*;
+
+    match peek(stream)? {
+        Some(('{', accept)) => accept(),
+        _ => return Ok(None),
+    };
+
+    if depth >= MAX_DEPTH {
+        // adversarially deep nesting isn't something resync can route around
+        // - bail out fatally, same as a raw I/O failure
+        return Err(stream.error(ExceededMaxDepth));
+    }
+
+    read_nws(stream)?;
+
+    Ok(Some(read_level_inner(
+        stream,
+        true,
+        depth + 1,
+        duplicate_keys,
+        errors,
+    )?))
+}
+
+fn read_level_inner(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+    level_ends_with_close: bool,
+    depth: usize,
+    duplicate_keys: DuplicateKeyPolicy,
+    errors: &mut Vec<ParseError>,
+) -> Result<Level, ParseError> {
+    use ParseErrorKind::*;
+
+    let mut results = Level::default();
+    Ok(loop {
+        if level_ends_with_close {
+            if let Some(('}', accept)) = peek(stream)? {
+                accept();
+                break results;
+            }
+        }
+
+        let key_pos = stream.pos;
+        let key = match read_key_path(stream, errors)? {
+            Some(key) => key,
+            None if level_ends_with_close => {
+                errors.push(stream.error(ExpectedKeyOrEnd));
+                if peek(stream)?.is_none() {
+                    break results;
+                }
+                skip_to_resync(stream, &['}', '\r', '\n'])?;
+                read_nws(stream)?;
+                continue;
+            },
+            // clean (or otherwise) end of root content - whatever is left
+            // over is reported by the caller, as `read_root` already does
+            None => break results,
+        };
+
+        read_nws(stream)?;
+
+        let lsd = match read_lsd(stream, Some('}'), depth, duplicate_keys, errors)? {
+            Some(lsd) => lsd,
+            None => {
+                errors.push(stream.error(ExpectedLSDAfterKey));
+                LSD::Value(Value::new())
+            },
+        };
+
+        read_nws(stream)?;
+
+        fn merge_level(
+            insert_into: &mut Level,
+            level: Level,
+            err_pos: Span,
+            duplicate_keys: DuplicateKeyPolicy,
+            errors: &mut Vec<ParseError>,
+        ) {
+            for (key, value) in level.into_iter() {
+                if matches!(value, LSD::Level(_)) {
+                    let LSD::Level(lvl) = value else {
+                        unreachable!()
+                    };
+                    match insert_into
+                        .entry(key)
+                        .or_insert_with(|| LSD::Level(Level::default()))
+                    {
+                        LSD::Value(_) | LSD::List(_) => errors.push(ParseError {
+                            kind: KeyCollisionShouldBeLevelButIsNot,
+                            span: err_pos,
+                        }),
+                        LSD::Level(ref mut insert_into) =>
+                            merge_level(insert_into, lvl, err_pos, duplicate_keys, errors),
+                    }
+                    continue;
+                }
+
+                match insert_into.entry(key.clone()) {
+                    indexmap::map::Entry::Vacant(entry) => {
+                        entry.insert(value);
+                    },
+                    indexmap::map::Entry::Occupied(mut entry) => match duplicate_keys {
+                        DuplicateKeyPolicy::Error => errors.push(ParseError {
+                            kind: KeyCollisionKeyAlreadyExists(key),
+                            span: err_pos,
+                        }),
+                        DuplicateKeyPolicy::FirstWins => {},
+                        DuplicateKeyPolicy::LastWins => {
+                            entry.insert(value);
+                        },
+                        DuplicateKeyPolicy::MergeLists => match (entry.get_mut(), value) {
+                            (LSD::List(existing), LSD::List(new)) => existing.extend(new),
+                            (existing, value) => *existing = value,
+                        },
+                    },
+                }
+            }
+        }
+
+        // wrap key-lsd pair in key parts
+        let mut result = Level::new();
+        let mut insert_into = &mut result;
+
+        for (i, part) in key
+            .iter()
+            .enumerate()
+        {
+            let part = part
+                .as_str()
+                .into();
+
+            if key.len() - 1 == i {
+                insert_into.insert(part, lsd);
+                break;
+            }
+
+            insert_into = match insert_into
+                .entry(part)
+                .or_insert_with(|| LSD::Level(Level::default()))
+            {
+                LSD::Level(ref mut lvl) => lvl,
+                _ => unreachable!(),
+            }
+        }
+
+        merge_level(&mut results, result, key_pos, duplicate_keys, errors);
+    })
+}
+
+fn read_key_word(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+) -> Result<Option<String>, ParseError> {
+    let mut result = String::new();
+    Ok(loop {
+        match peek(stream)? {
+            None
+            | Some((
+                ' ' | '\t' | '\r' | '\n' | '\'' | '"' | '#' | '{' | '}' | '[' | ']' | '.',
+                _,
+            )) =>
+                break result
+                    .is_empty()
+                    .not()
+                    .then_some(result),
+            Some((_, accept)) => result.push(accept()),
+        }
+    })
+}
+
+fn read_key_part(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+    errors: &mut Vec<ParseError>,
+) -> Result<Option<String>, ParseError> {
+    let mut result = String::new();
+    loop {
+        if let Some(word) = read_key_word(stream)? {
+            result.push_str(&word);
+            continue;
+        }
+
+        if let Some(string) = read_string(stream, errors)? {
+            result.push_str(&string);
+            continue;
+        }
+
+        break Ok(result
+            .is_empty()
+            .not()
+            .then_some(result));
+    }
+}
+
+/// Read a key path (separated by `.`) from the stream. A trailing separator
+/// with nothing after it is recorded and the path is cut short, rather than
+/// aborting.
+fn read_key_path(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+    errors: &mut Vec<ParseError>,
+) -> Result<Option<Vec<String>>, ParseError> {
+    use ParseErrorKind::*;
+
+    let mut result = vec![match read_key_part(stream, errors)? {
+        Some(key_part) => key_part,
+        None => return Ok(None),
+    }];
+
+    loop {
+        let Some(('.', accept)) = peek(stream)? else {
+            break;
+        };
+        accept();
+
+        match read_key_part(stream, errors)? {
+            Some(part) => result.push(part),
+            None => {
+                errors.push(stream.error(ExpectedKeyPartAfterKeySeparator));
+                break;
+            },
+        }
+    }
+
+    Ok(Some(result))
+}
+
+fn read_list_lsd(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+    depth: usize,
+    duplicate_keys: DuplicateKeyPolicy,
+    errors: &mut Vec<ParseError>,
+) -> Result<Option<LSD>, ParseError> {
+    if let Some(list) = read_list(stream, depth, duplicate_keys, errors)? {
+        return Ok(Some(LSD::List(list)));
+    }
+
+    if let Some(level) = read_level(stream, depth, duplicate_keys, errors)? {
+        return Ok(Some(LSD::Level(level)));
+    }
+
+    if let Some(value) = read_list_value(stream, errors)? {
+        return Ok(Some(LSD::Value(value)));
+    }
+
+    Ok(None)
+}
+
+fn read_list_value(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+    errors: &mut Vec<ParseError>,
+) -> Result<Option<Value>, ParseError> {
+    let Some(mut result) = read_key_part(stream, errors)? else {
+        return Ok(None);
+    };
+
+    Ok(Some(loop {
+        let iws = read_iws(stream)?;
+        match read_key_part(stream, errors)? {
+            Some(part) => {
+                result.push_str(&iws);
+                result.push_str(&part);
+            },
+            None => break result,
+        }
+    }))
+}
+
+fn read_list(
+    stream: &mut Reader<impl Iterator<Item = io::Result<char>>>,
+    depth: usize,
+    duplicate_keys: DuplicateKeyPolicy,
+    errors: &mut Vec<ParseError>,
+) -> Result<Option<List>, ParseError> {
+    use ParseErrorKind::*;
+
+    match peek(stream)? {
+        Some(('[', accept)) => accept(),
+        _ => return Ok(None),
+    };
+
+    if depth >= MAX_DEPTH {
+        // adversarially deep nesting isn't something resync can route around
+        // - bail out fatally, same as a raw I/O failure
+        return Err(stream.error(ExceededMaxDepth));
+    }
+
+    read_nws(stream)?;
+
+    let mut results = List::default();
+    Ok(Some(loop {
+        if let Some((']', accept)) = peek(stream)? {
+            accept();
+            break results;
+        }
+
+        match read_list_lsd(stream, depth + 1, duplicate_keys, errors)? {
+            Some(lsd) => results.push(lsd),
+            None => {
+                errors.push(stream.error(ExpectedListLSDOrEnd));
+                if peek(stream)?.is_none() {
+                    break results;
+                }
+                skip_to_resync(stream, &[']', '\r', '\n'])?;
+            },
+        }
+
+        read_nws(stream)?;
+    }))
+}
@@ -0,0 +1,708 @@
+//! Optional `serde` support, enabled via the `serde` feature.
+//!
+//! Maps the LSD data model onto serde's data model the obvious way:
+//! [Level](crate::Level) and [List](crate::List) become serde maps/structs
+//! and seqs/tuples respectively, and [Value](crate::Value) is parsed lazily
+//! into whatever scalar serde asks for. Since LSD has no null and no typed
+//! scalars, `Option::None` serializes as an absent key (there is nothing to
+//! write in its place) and numbers/bools serialize as their textual [Value]
+//! form.
+
+use std::fmt::Display;
+use std::io;
+
+use serde::de;
+use serde::de::IntoDeserializer;
+use serde::ser;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::Level;
+use crate::List;
+use crate::ParseError;
+use crate::Value;
+use crate::LSD;
+
+/// Errors thrown while serializing to or deserializing from LSD via serde.
+#[derive(Debug)]
+pub enum Error {
+    /// The underlying text failed to parse as LSD at all.
+    Parse(ParseError),
+
+    /// A key was missing while deserializing a struct.
+    MissingField(&'static str),
+
+    /// An [LSD] node was not of the kind serde asked for (e.g. a struct
+    /// expected a [Level](crate::LSD::Level) but found a
+    /// [Value](crate::LSD::Value)).
+    TypeMismatch {
+        expected: &'static str,
+    },
+
+    /// Anything else, usually raised by `#[derive(Deserialize)]`-generated
+    /// code via [de::Error::custom]/[ser::Error::custom].
+    Custom(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Parse(err) => write!(f, "failed to parse LSD: {err:?}"),
+            Error::MissingField(field) => write!(f, "missing field `{field}`"),
+            Error::TypeMismatch { expected } => write!(f, "expected {expected}"),
+            Error::Custom(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self { Error::Custom(msg.to_string()) }
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self { Error::Custom(msg.to_string()) }
+
+    fn missing_field(field: &'static str) -> Self { Error::MissingField(field) }
+}
+
+//
+// Serializer
+//
+
+/// Serializes any [Serialize] value into an [LSD] tree.
+///
+/// `None` and `Ok(None)` both mean "nothing to write here" (there is no LSD
+/// representation of null), which is how `Option::None` fields end up
+/// dropped from the enclosing [Level] entirely.
+struct Serializer;
+
+macro_rules! serialize_display {
+    ($($method:ident: $ty:ty),* $(,)?) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Self::Ok, Self::Error> {
+                Ok(Some(LSD::Value(v.to_string())))
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Option<LSD>;
+    type Error = Error;
+
+    type SerializeSeq = SerializeVec;
+    type SerializeTuple = SerializeVec;
+    type SerializeTupleStruct = SerializeVec;
+    type SerializeTupleVariant = SerializeVariant<SerializeVec>;
+    type SerializeMap = SerializeLevel;
+    type SerializeStruct = SerializeLevel;
+    type SerializeStructVariant = SerializeVariant<SerializeLevel>;
+
+    serialize_display!(
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_i128: i128,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_u128: u128,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char,
+    );
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(LSD::Value(v.to_string())))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Err(Error::Custom(
+            "LSD has no way to represent raw bytes".to_string(),
+        ))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> { Ok(None) }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(LSD::Level(Level::default())))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(LSD::Value(variant.to_string())))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        let inner = value
+            .serialize(self)?
+            .unwrap_or_else(|| LSD::Level(Level::default()));
+        Ok(Some(LSD::Level(Level::from([(
+            variant.to_string(),
+            inner,
+        )]))))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(SerializeVec { items: List::new() })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(SerializeLevel {
+            level: Level::new(),
+            pending_key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(SerializeLevel {
+            level: Level::new(),
+            pending_key: None,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_struct(variant, len)?,
+        })
+    }
+}
+
+/// Serializes a value that must turn into a [Value] (used for map/struct
+/// keys, which LSD always stores as strings).
+struct KeySerializer;
+
+impl KeySerializer {
+    fn key_of(lsd: Option<LSD>) -> Result<String, Error> {
+        match lsd {
+            Some(LSD::Value(value)) => Ok(value),
+            _ => Err(Error::Custom(
+                "map keys must serialize to a plain value".to_string(),
+            )),
+        }
+    }
+}
+
+struct SerializeVec {
+    items: List,
+}
+
+impl ser::SerializeSeq for SerializeVec {
+    type Ok = Option<LSD>;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let item = value
+            .serialize(Serializer)?
+            .ok_or_else(|| Error::Custom("LSD lists cannot contain an absent item".to_string()))?;
+        self.items
+            .push(item);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> { Ok(Some(LSD::List(self.items))) }
+}
+
+impl ser::SerializeTuple for SerializeVec {
+    type Ok = Option<LSD>;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> { ser::SerializeSeq::end(self) }
+}
+
+impl ser::SerializeTupleStruct for SerializeVec {
+    type Ok = Option<LSD>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> { ser::SerializeSeq::end(self) }
+}
+
+struct SerializeLevel {
+    level: Level,
+    pending_key: Option<String>,
+}
+
+impl ser::SerializeMap for SerializeLevel {
+    type Ok = Option<LSD>;
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
+        self.pending_key = Some(KeySerializer::key_of(key.serialize(Serializer)?)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let key = self
+            .pending_key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        if let Some(value) = value.serialize(Serializer)? {
+            self.level
+                .insert(key, value);
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> { Ok(Some(LSD::Level(self.level))) }
+}
+
+impl ser::SerializeStruct for SerializeLevel {
+    type Ok = Option<LSD>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        if let Some(value) = value.serialize(Serializer)? {
+            self.level
+                .insert(key.to_string(), value);
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> { Ok(Some(LSD::Level(self.level))) }
+}
+
+/// Wraps a seq/map serializer to produce the externally-tagged
+/// `{ variant: ... }` shape used for enum variants carrying data.
+struct SerializeVariant<Inner> {
+    variant: &'static str,
+    inner: Inner,
+}
+
+impl ser::SerializeTupleVariant for SerializeVariant<SerializeVec> {
+    type Ok = Option<LSD>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        let items = ser::SerializeSeq::end(self.inner)?
+            .unwrap_or_else(|| LSD::List(List::new()));
+        Ok(Some(LSD::Level(Level::from([(
+            self.variant
+                .to_string(),
+            items,
+        )]))))
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeVariant<SerializeLevel> {
+    type Ok = Option<LSD>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        let fields = ser::SerializeStruct::end(self.inner)?
+            .unwrap_or_else(|| LSD::Level(Level::new()));
+        Ok(Some(LSD::Level(Level::from([(
+            self.variant
+                .to_string(),
+            fields,
+        )]))))
+    }
+}
+
+//
+// Deserializer
+//
+
+/// Deserializes any [Deserialize] type from a borrowed [LSD] tree.
+struct LsdDeserializer<'de> {
+    lsd: &'de LSD,
+}
+
+impl<'de> LsdDeserializer<'de> {
+    fn as_value(&self) -> Result<&'de Value, Error> {
+        self.lsd
+            .as_value()
+            .ok_or(Error::TypeMismatch { expected: "a value" })
+    }
+
+    fn parsed<T: std::str::FromStr>(&self, expected: &'static str) -> Result<T, Error> {
+        self.as_value()?
+            .parse()
+            .map_err(|_| Error::TypeMismatch { expected })
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident: $ty:ty => $visit:ident),* $(,)?) => {
+        $(
+            fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                visitor.$visit(self.parsed::<$ty>(stringify!($ty))?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for LsdDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.lsd {
+            LSD::Value(value) => visitor.visit_borrowed_str(value),
+            LSD::List(_) => self.deserialize_seq(visitor),
+            LSD::Level(_) => self.deserialize_map(visitor),
+        }
+    }
+
+    deserialize_parsed!(
+        deserialize_bool: bool => visit_bool,
+        deserialize_i8: i8 => visit_i8,
+        deserialize_i16: i16 => visit_i16,
+        deserialize_i32: i32 => visit_i32,
+        deserialize_i64: i64 => visit_i64,
+        deserialize_i128: i128 => visit_i128,
+        deserialize_u8: u8 => visit_u8,
+        deserialize_u16: u16 => visit_u16,
+        deserialize_u32: u32 => visit_u32,
+        deserialize_u64: u64 => visit_u64,
+        deserialize_u128: u128 => visit_u128,
+        deserialize_f32: f32 => visit_f32,
+        deserialize_f64: f64 => visit_f64,
+        deserialize_char: char => visit_char,
+    );
+
+    fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.as_value()?)
+    }
+
+    fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_bytes(
+            self.as_value()?
+                .as_bytes(),
+        )
+    }
+
+    fn deserialize_byte_buf<V: de::Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        // A missing key never reaches here (the field is left as None by the
+        // containing MapAccess); a present node is always Some.
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.lsd {
+            LSD::Level(level) if level.is_empty() => visitor.visit_unit(),
+            _ => Err(Error::TypeMismatch { expected: "an empty level" }),
+        }
+    }
+
+    fn deserialize_unit_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let list = self
+            .lsd
+            .as_list()
+            .ok_or(Error::TypeMismatch { expected: "a list" })?;
+        visitor.visit_seq(SeqAccess {
+            iter: list.iter(),
+        })
+    }
+
+    fn deserialize_tuple<V: de::Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let level = self
+            .lsd
+            .as_level()
+            .ok_or(Error::TypeMismatch { expected: "a level" })?;
+        visitor.visit_map(MapAccess {
+            iter: level.iter(),
+            value: None,
+        })
+    }
+
+    fn deserialize_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.lsd {
+            LSD::Value(variant) => visitor.visit_enum(variant.as_str().into_deserializer()),
+            LSD::Level(level) => {
+                let (variant, value) = level
+                    .iter()
+                    .next()
+                    .ok_or(Error::TypeMismatch { expected: "a single-key level naming a variant" })?;
+                visitor.visit_enum(EnumAccess { variant, value })
+            },
+            LSD::List(_) => Err(Error::TypeMismatch { expected: "an enum variant" }),
+        }
+    }
+
+    fn deserialize_identifier<V: de::Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V: de::Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+}
+
+struct SeqAccess<'de> {
+    iter: std::slice::Iter<'de, LSD>,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        self.iter
+            .next()
+            .map(|lsd| seed.deserialize(LsdDeserializer { lsd }))
+            .transpose()
+    }
+}
+
+struct MapAccess<'de> {
+    iter: indexmap::map::Iter<'de, Value, LSD>,
+    value: Option<&'de LSD>,
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self
+            .iter
+            .next()
+        {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(key.as_str().into_deserializer())
+                    .map(Some)
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let lsd = self
+            .value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        seed.deserialize(LsdDeserializer { lsd })
+    }
+}
+
+struct EnumAccess<'de> {
+    variant: &'de str,
+    value: &'de LSD,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess<'de> {
+    type Error = Error;
+    type Variant = LsdDeserializer<'de>;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, LsdDeserializer { lsd: self.value }))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for LsdDeserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> { Ok(()) }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: de::Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: de::Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_struct(self, "", fields, visitor)
+    }
+}
+
+//
+// Entry points
+//
+
+/// Serialize any [Serialize] value as LSD text.
+pub fn to_string<T: Serialize>(value: &T) -> Result<String, Error> {
+    let lsd = value
+        .serialize(Serializer)?
+        .unwrap_or_else(|| LSD::Level(Level::default()));
+    Ok(lsd.to_string_pretty(4))
+}
+
+/// Deserialize any [Deserialize] type from an LSD [Read](io::Read) stream.
+pub fn from_reader<T: for<'de> Deserialize<'de>>(reader: impl io::Read) -> Result<T, Error> {
+    let lsd = LSD::parse(reader).map_err(Error::Parse)?;
+    T::deserialize(LsdDeserializer { lsd: &lsd })
+}
+
+/// Deserialize any [Deserialize] type from LSD text.
+pub fn from_str<T: for<'de> Deserialize<'de>>(s: &str) -> Result<T, Error> {
+    from_reader(s.as_bytes())
+}
@@ -0,0 +1,89 @@
+use std::io::Cursor;
+
+use lsdata::key;
+use lsdata::DuplicateKeyPolicy;
+use lsdata::LSD;
+
+#[test]
+fn matches_plain_parse() {
+    let text = "
+        # leading comment
+        a 10
+        b { c 20 }
+    ";
+
+    let plain = LSD::parse(Cursor::new(text)).unwrap();
+    let (annotated, _) = LSD::parse_with_annotations(Cursor::new(text), DuplicateKeyPolicy::Error).unwrap();
+
+    assert_eq!(annotated, plain);
+}
+
+#[test]
+fn comment_is_attached_to_following_key() {
+    let text = "
+        # about a
+        a 10
+        b 20
+    ";
+
+    let (_, comments) = LSD::parse_with_annotations(Cursor::new(text), DuplicateKeyPolicy::Error).unwrap();
+
+    assert_eq!(
+        comments
+            .get(&key!["a"])
+            .map(Vec::as_slice),
+        Some(&[" about a".to_string()][..]),
+    );
+    assert_eq!(comments.get(&key!["b"]), None);
+}
+
+#[test]
+fn comment_is_attached_to_nested_key() {
+    let text = "
+        a {
+            # about a.c
+            c 20
+        }
+    ";
+
+    let (_, comments) = LSD::parse_with_annotations(Cursor::new(text), DuplicateKeyPolicy::Error).unwrap();
+
+    assert_eq!(
+        comments
+            .get(&key!["a" "c"])
+            .map(Vec::as_slice),
+        Some(&[" about a.c".to_string()][..]),
+    );
+}
+
+#[test]
+fn comment_is_attached_to_list_item() {
+    let text = "
+        a [
+            1
+            # about the second item
+            2
+        ]
+    ";
+
+    let (_, comments) = LSD::parse_with_annotations(Cursor::new(text), DuplicateKeyPolicy::Error).unwrap();
+
+    assert_eq!(
+        comments
+            .get(&key!["a" 1])
+            .map(Vec::as_slice),
+        Some(&[" about the second item".to_string()][..]),
+    );
+}
+
+#[test]
+fn trailing_comment_is_dropped() {
+    let text = "
+        a 10
+        # trailing, attached to nothing
+    ";
+
+    let (_, comments) = LSD::parse_with_annotations(Cursor::new(text), DuplicateKeyPolicy::Error).unwrap();
+
+    assert!(comments.is_empty());
+}
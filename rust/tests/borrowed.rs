@@ -0,0 +1,65 @@
+use std::borrow::Cow;
+use std::io::Cursor;
+
+use lsdata::DuplicateKeyPolicy;
+use lsdata::LSDRef;
+use lsdata::LSD;
+
+#[test]
+fn matches_owned_parse() {
+    let text = r#"
+        a 10
+        b { c 20 }
+        d [ 1 2 "esc\nape" ]
+    "#;
+
+    let owned = LSD::parse(Cursor::new(text)).unwrap();
+    let borrowed = LSD::parse_str(text, DuplicateKeyPolicy::Error)
+        .unwrap()
+        .into_owned();
+
+    assert_eq!(borrowed, owned);
+}
+
+#[test]
+fn plain_word_is_borrowed() {
+    let lsd = LSD::parse_str("a 10", DuplicateKeyPolicy::Error).unwrap();
+    let LSDRef::Level(level) = lsd else {
+        panic!("expected a level");
+    };
+    assert!(matches!(
+        level
+            .get("a")
+            .unwrap(),
+        LSDRef::Value(Cow::Borrowed("10"))
+    ));
+}
+
+#[test]
+fn unescaped_string_is_borrowed() {
+    let lsd = LSD::parse_str(r#"a "plain string""#, DuplicateKeyPolicy::Error).unwrap();
+    let LSDRef::Level(level) = lsd else {
+        panic!("expected a level");
+    };
+    assert!(matches!(
+        level
+            .get("a")
+            .unwrap(),
+        LSDRef::Value(Cow::Borrowed("plain string"))
+    ));
+}
+
+#[test]
+fn escaped_string_allocates() {
+    let lsd = LSD::parse_str(r#"a "esc\nape""#, DuplicateKeyPolicy::Error).unwrap();
+    let LSDRef::Level(level) = lsd else {
+        panic!("expected a level");
+    };
+    match level
+        .get("a")
+        .unwrap()
+    {
+        LSDRef::Value(Cow::Owned(value)) => assert_eq!(value, "esc\nape"),
+        other => panic!("expected an owned escaped value, got {other:?}"),
+    }
+}
@@ -0,0 +1,180 @@
+use std::io::Cursor;
+
+use lsdata::DuplicateKeyPolicy;
+use lsdata::Level;
+use lsdata::List;
+use lsdata::ParseErrorKind::*;
+use lsdata::ParseOptions;
+use lsdata::LSD;
+
+#[test]
+fn default_matches_parse() {
+    let text = "a 10";
+
+    let default = LSD::parser()
+        .parse(Cursor::new(text))
+        .unwrap();
+    let parse = LSD::parse(Cursor::new(text)).unwrap();
+
+    assert_eq!(default, parse);
+}
+
+#[test]
+fn duplicate_keys_error_by_default() {
+    let err = LSD::parse(Cursor::new("a 10\na 20")).unwrap_err();
+    assert!(matches!(err.kind, KeyCollisionKeyAlreadyExists(key) if key == "a"));
+}
+
+#[test]
+fn duplicate_keys_first_wins() {
+    let lsd = LSD::parser()
+        .duplicate_keys(DuplicateKeyPolicy::FirstWins)
+        .parse(Cursor::new("a 10\na 20"))
+        .unwrap();
+
+    assert_eq!(
+        lsd.as_level()
+            .unwrap()
+            .get("a")
+            .unwrap()
+            .as_value()
+            .unwrap(),
+        "10",
+    );
+}
+
+#[test]
+fn duplicate_keys_last_wins() {
+    let lsd = LSD::parser()
+        .duplicate_keys(DuplicateKeyPolicy::LastWins)
+        .parse(Cursor::new("a 10\na 20"))
+        .unwrap();
+
+    assert_eq!(
+        lsd.as_level()
+            .unwrap()
+            .get("a")
+            .unwrap()
+            .as_value()
+            .unwrap(),
+        "20",
+    );
+}
+
+#[test]
+fn duplicate_keys_merge_lists() {
+    let lsd = LSD::parser()
+        .duplicate_keys(DuplicateKeyPolicy::MergeLists)
+        .parse(Cursor::new("a [ 1 ]\na [ 2 ]"))
+        .unwrap();
+
+    assert_eq!(
+        lsd.as_level()
+            .unwrap()
+            .get("a")
+            .unwrap()
+            .as_list()
+            .unwrap(),
+        &List::from([
+            LSD::Value("1".to_string()),
+            LSD::Value("2".to_string()),
+        ]),
+    );
+}
+
+#[test]
+fn comments_disabled_allows_hash_in_words() {
+    let lsd = LSD::parser()
+        .comments(false)
+        .parse(Cursor::new("a #not-a-comment"))
+        .unwrap();
+
+    assert_eq!(
+        lsd,
+        LSD::Level(Level::from([(
+            "a".to_string(),
+            LSD::Value("#not-a-comment".to_string()),
+        )])),
+    );
+}
+
+#[test]
+fn max_depth_rejects_deep_nesting() {
+    let err = LSD::parser()
+        .max_depth(1)
+        .parse(Cursor::new("a { b { c 10 } }"))
+        .unwrap_err();
+
+    assert!(matches!(err.kind, ExceededMaxDepth));
+}
+
+#[test]
+fn max_depth_allows_shallow_nesting() {
+    let lsd = LSD::parser()
+        .max_depth(1)
+        .parse(Cursor::new("a { b 10 }"))
+        .unwrap();
+
+    assert_eq!(
+        lsd,
+        LSD::Level(Level::from([(
+            "a".to_string(),
+            LSD::Level(Level::from([(
+                "b".to_string(),
+                LSD::Value("10".to_string()),
+            )])),
+        )])),
+    );
+}
+
+#[test]
+fn parse_with_default_options_bounds_depth_at_128() {
+    let deeply_nested = "[".repeat(129) + &"]".repeat(129);
+
+    let err = LSD::parse_with(ParseOptions::default(), Cursor::new(deeply_nested)).unwrap_err();
+
+    assert!(matches!(err.kind, ExceededMaxDepth));
+}
+
+#[test]
+fn parse_with_custom_max_depth() {
+    let lsd = LSD::parse_with(
+        ParseOptions { max_depth: 1 },
+        Cursor::new("a { b 10 }"),
+    )
+    .unwrap();
+
+    assert_eq!(
+        lsd,
+        LSD::Level(Level::from([(
+            "a".to_string(),
+            LSD::Level(Level::from([(
+                "b".to_string(),
+                LSD::Value("10".to_string()),
+            )])),
+        )])),
+    );
+}
+
+#[test]
+fn parse_with_matches_parser_builder_with_same_max_depth() {
+    let text = "a { b 10 }";
+
+    let parse_with = LSD::parse_with(ParseOptions { max_depth: 1 }, Cursor::new(text)).unwrap();
+    let builder = LSD::parser()
+        .max_depth(1)
+        .parse(Cursor::new(text))
+        .unwrap();
+
+    assert_eq!(parse_with, builder);
+}
+
+#[test]
+fn allow_trailing_root_content() {
+    let lsd = LSD::parser()
+        .allow_trailing_root_content(true)
+        .parse(Cursor::new("{} garbage"))
+        .unwrap();
+
+    assert_eq!(lsd, LSD::Level(Level::default()));
+}
@@ -1,67 +1,76 @@
 use std::io::Cursor;
 
-use lsdata::ParseError::*;
+use lsdata::ParseErrorKind::*;
 use lsdata::LSD;
 
 #[test]
 fn unexpected_char_at_file_end() {
     assert!(matches!(
-        LSD::parse(Cursor::new(r#"[] test"#)),
-        Err(UnexpectedCharAtFileEnd)
+        LSD::parse(Cursor::new(r#"[] test"#)).unwrap_err().kind,
+        UnexpectedCharAtFileEnd
     ));
     assert!(matches!(
-        LSD::parse(Cursor::new(r#"{} test"#)),
-        Err(UnexpectedCharAtFileEnd)
+        LSD::parse(Cursor::new(r#"{} test"#)).unwrap_err().kind,
+        UnexpectedCharAtFileEnd
     ));
 }
 
 #[test]
 fn unexpected_string_end() {
     assert!(matches!(
-        LSD::parse(Cursor::new(r#"test ""#)),
-        Err(UnexpectedStringEnd)
+        LSD::parse(Cursor::new(r#"test ""#)).unwrap_err().kind,
+        UnexpectedStringEnd
     ));
     assert!(matches!(
-        LSD::parse(Cursor::new(r#"test "\u"#)),
-        Err(UnexpectedStringEnd)
+        LSD::parse(Cursor::new(r#"test "\u"#)).unwrap_err().kind,
+        UnexpectedStringEnd
     ));
     assert!(matches!(
-        LSD::parse(Cursor::new(r#"test "\udfff"#)),
-        Err(UnexpectedStringEnd)
+        LSD::parse(Cursor::new(r#"test "\udfff"#)).unwrap_err().kind,
+        UnexpectedStringEnd
     ));
     assert!(matches!(
-        LSD::parse(Cursor::new(r#"test "\x"#)),
-        Err(UnexpectedStringEnd)
+        LSD::parse(Cursor::new(r#"test "\x"#)).unwrap_err().kind,
+        UnexpectedStringEnd
     ));
     assert!(matches!(
-        LSD::parse(Cursor::new(r#"test "\xff"#)),
-        Err(UnexpectedStringEnd)
+        LSD::parse(Cursor::new(r#"test "\xff"#)).unwrap_err().kind,
+        UnexpectedStringEnd
     ));
 }
 
+#[test]
+fn unexpected_string_end_points_at_string_start() {
+    let err = LSD::parse(Cursor::new("test \"abc")).unwrap_err();
+    assert!(matches!(err.kind, UnexpectedStringEnd));
+    assert_eq!(err.span.col, 6);
+}
+
 #[test]
 fn unexpected_char_escape_end() {
     assert!(matches!(
-        LSD::parse(Cursor::new(r#"test "\"#)),
-        Err(UnexpectedCharEscapeEnd)
+        LSD::parse(Cursor::new(r#"test "\"#)).unwrap_err().kind,
+        UnexpectedCharEscapeEnd
     ));
     assert!(matches!(
-        LSD::parse(Cursor::new(r#"test "\j"#)),
-        Err(UnexpectedCharEscapeEnd)
+        LSD::parse(Cursor::new(r#"test "\j"#)).unwrap_err().kind,
+        UnexpectedCharEscapeEnd
     ));
 }
 
 #[test]
 fn unexpected_char_in_byte_escape() {
     assert!(matches!(
-        LSD::parse(Cursor::new(r#"test "\xffNO"#)),
-        Err(UnexpectedCharInByteEscape)
+        LSD::parse(Cursor::new(r#"test "\xffNO"#)).unwrap_err().kind,
+        UnexpectedCharInByteEscape
     ));
     assert!(matches!(
         LSD::parse(Cursor::new(
             r#"test "\xf0\x00\x00\x00\x00""#
-        )),
-        Err(UnexpectedCharInByteEscape)
+        ))
+        .unwrap_err()
+        .kind,
+        UnexpectedCharInByteEscape
     ));
 }
 
@@ -70,46 +79,50 @@ fn unexpected_char_in_unicode_escape() {
     assert!(matches!(
         LSD::parse(Cursor::new(
             r#"test "\udfffNO""#
-        )),
-        Err(UnexpectedCharInUnicodeEscape)
+        ))
+        .unwrap_err()
+        .kind,
+        UnexpectedCharInUnicodeEscape
     ));
     assert!(matches!(
         LSD::parse(Cursor::new(
             r#"test "\udfff\udfff""#
-        )),
-        Err(UnexpectedCharInUnicodeEscape)
+        ))
+        .unwrap_err()
+        .kind,
+        UnexpectedCharInUnicodeEscape
     ));
 }
 
 #[test]
 fn expected_key_or_end() {
     assert!(matches!(
-        LSD::parse(Cursor::new(r#"{"#)),
-        Err(ExpectedKeyOrEnd)
+        LSD::parse(Cursor::new(r#"{"#)).unwrap_err().kind,
+        ExpectedKeyOrEnd
     ));
 }
 
 #[test]
 fn expected_key_part_after_key_separator() {
     assert!(matches!(
-        LSD::parse(Cursor::new(r#"{a."#)),
-        Err(ExpectedKeyPartAfterKeySeparator)
+        LSD::parse(Cursor::new(r#"{a."#)).unwrap_err().kind,
+        ExpectedKeyPartAfterKeySeparator
     ));
 }
 
 #[test]
 fn expected_lsd_after_key() {
     assert!(matches!(
-        LSD::parse(Cursor::new(r#"{a "#)),
-        Err(ExpectedLSDAfterKey)
+        LSD::parse(Cursor::new(r#"{a "#)).unwrap_err().kind,
+        ExpectedLSDAfterKey
     ));
 }
 
 #[test]
 fn expected_list_item_or_end() {
     assert!(matches!(
-        LSD::parse(Cursor::new(r#"["#)),
-        Err(ExpectedListLSDOrEnd)
+        LSD::parse(Cursor::new(r#"["#)).unwrap_err().kind,
+        ExpectedListLSDOrEnd
     ));
 }
 
@@ -121,8 +134,10 @@ fn key_collision_should_be_level_but_is_not() {
                 a 10
                 a.b 20
             "#
-        )),
-        Err(KeyCollisionShouldBeLevelButIsNot)
+        ))
+        .unwrap_err()
+        .kind,
+        KeyCollisionShouldBeLevelButIsNot
     ));
 }
 
@@ -134,9 +149,9 @@ fn key_collision_key_already_exists() {
                 a 10
                 a 20
             "#
-        )),
-        Err(KeyCollisionKeyAlreadyExists(
-            ..
         ))
+        .unwrap_err()
+        .kind,
+        KeyCollisionKeyAlreadyExists(..)
     ));
 }
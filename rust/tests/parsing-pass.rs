@@ -200,3 +200,28 @@ fn nested_list() {
         ]))
     );
 }
+
+#[test]
+fn unicode_escape() {
+    // astral's \ud83d high surrogate only decodes once paired with the
+    // \ude00 low surrogate that follows it
+    let text = Cursor::new(
+        "bmp \"caf\\u00e9\"\nastral \"\\ud83d\\ude00\"",
+    );
+
+    let lsd = LSD::parse(text).unwrap();
+
+    assert_eq!(
+        lsd,
+        LSD::Level(Level::from([
+            (
+                "bmp".to_string(),
+                LSD::Value("café".to_string())
+            ),
+            (
+                "astral".to_string(),
+                LSD::Value("😀".to_string())
+            ),
+        ]))
+    );
+}
@@ -0,0 +1,101 @@
+use std::io::Cursor;
+
+use lsdata::DuplicateKeyPolicy;
+use lsdata::Level;
+use lsdata::ParseErrorKind::*;
+use lsdata::LSD;
+
+#[test]
+fn matches_parse_on_valid_input() {
+    let text = "a 10\nb {\n    c 20\n}";
+
+    let (lsd, errors) = LSD::parse_recovering(Cursor::new(text), DuplicateKeyPolicy::Error);
+
+    assert!(errors.is_empty());
+    assert_eq!(lsd, LSD::parse(Cursor::new(text)).unwrap());
+}
+
+#[test]
+fn unterminated_level_is_reported_but_keeps_prior_keys() {
+    let (lsd, errors) = LSD::parse_recovering(Cursor::new("a 10\nb {\nc 20"), DuplicateKeyPolicy::Error);
+
+    assert!(matches!(errors[0].kind, ExpectedKeyOrEnd));
+    assert_eq!(
+        lsd.as_level()
+            .unwrap()
+            .get("a")
+            .unwrap()
+            .as_value()
+            .unwrap(),
+        "10",
+    );
+    assert_eq!(
+        lsd.as_level()
+            .unwrap()
+            .get("b")
+            .unwrap()
+            .as_level()
+            .unwrap()
+            .get("c")
+            .unwrap()
+            .as_value()
+            .unwrap(),
+        "20",
+    );
+}
+
+#[test]
+fn missing_value_after_key_becomes_an_empty_placeholder() {
+    let (lsd, errors) = LSD::parse_recovering(Cursor::new("a"), DuplicateKeyPolicy::Error);
+
+    assert!(matches!(errors[0].kind, ExpectedLSDAfterKey));
+    assert_eq!(
+        lsd,
+        LSD::Level(Level::from([(
+            "a".to_string(),
+            LSD::Value(String::new()),
+        )])),
+    );
+}
+
+#[test]
+fn duplicate_key_is_reported_and_first_value_wins() {
+    let (lsd, errors) = LSD::parse_recovering(Cursor::new("a 10\na 20"), DuplicateKeyPolicy::Error);
+
+    assert!(matches!(errors[0].kind, KeyCollisionKeyAlreadyExists(ref key) if key == "a"));
+    assert_eq!(
+        lsd.as_level()
+            .unwrap()
+            .get("a")
+            .unwrap()
+            .as_value()
+            .unwrap(),
+        "10",
+    );
+}
+
+#[test]
+fn malformed_unicode_escape_is_reported() {
+    let (_, errors) = LSD::parse_recovering(Cursor::new(r#"a "\udfffNO""#), DuplicateKeyPolicy::Error);
+
+    assert!(matches!(errors[0].kind, UnexpectedCharInUnicodeEscape));
+}
+
+#[test]
+fn unterminated_list_is_reported_but_keeps_items_read_so_far() {
+    let (lsd, errors) = LSD::parse_recovering(Cursor::new("a [\n1\n2"), DuplicateKeyPolicy::Error);
+
+    assert!(matches!(errors[0].kind, ExpectedListLSDOrEnd));
+    assert_eq!(
+        lsd.as_level()
+            .unwrap()
+            .get("a")
+            .unwrap()
+            .as_list()
+            .unwrap(),
+        &vec![
+            LSD::Value("1".to_string()),
+            LSD::Value("2".to_string()),
+        ],
+    );
+}
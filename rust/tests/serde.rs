@@ -0,0 +1,105 @@
+#![cfg(feature = "serde")]
+
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Config {
+    name: String,
+    port: u16,
+    server: Server,
+    tags: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Server {
+    host: String,
+}
+
+#[test]
+fn struct_from_str() {
+    let config: Config = lsdata::from_str(
+        r#"
+            name "example"
+            port 8080
+            server {
+                host "localhost"
+            }
+            tags [
+                "a"
+                "b"
+                "c"
+            ]
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        config,
+        Config {
+            name: "example".to_string(),
+            port: 8080,
+            server: Server { host: "localhost".to_string() },
+            tags: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        },
+    );
+}
+
+#[test]
+fn missing_field_is_a_descriptive_error() {
+    let err = lsdata::from_str::<Config>(
+        r#"
+            name "example"
+            server { host "localhost" }
+            tags []
+        "#,
+    )
+    .unwrap_err();
+
+    assert!(matches!(err, lsdata::SerdeError::MissingField("port")));
+}
+
+#[test]
+fn type_mismatch_is_a_descriptive_error() {
+    let err = lsdata::from_str::<Config>(
+        r#"
+            name "example"
+            port "not a number"
+            server { host "localhost" }
+            tags []
+        "#,
+    )
+    .unwrap_err();
+
+    assert!(matches!(err, lsdata::SerdeError::TypeMismatch { expected: "u16" }));
+}
+
+#[test]
+fn level_expected_but_found_value_is_a_type_mismatch() {
+    let err = lsdata::from_str::<Config>(
+        r#"
+            name "example"
+            port 8080
+            server "not a level"
+            tags []
+        "#,
+    )
+    .unwrap_err();
+
+    assert!(matches!(err, lsdata::SerdeError::TypeMismatch { expected: "a level" }));
+}
+
+#[test]
+fn round_trips_through_to_string() {
+    let config = Config {
+        name: "example".to_string(),
+        port: 8080,
+        server: Server { host: "localhost".to_string() },
+        tags: vec!["a".to_string(), "b".to_string()],
+    };
+
+    let text = lsdata::to_string(&config).unwrap();
+    let parsed: Config = lsdata::from_str(&text).unwrap();
+
+    assert_eq!(parsed, config);
+}
@@ -0,0 +1,134 @@
+use std::io::Cursor;
+
+use lsdata::Level;
+use lsdata::List;
+use lsdata::LSD;
+
+fn round_trip(lsd: &LSD) {
+    let written = lsd.to_string_pretty(4);
+    let reparsed = LSD::parse(Cursor::new(written.as_str())).unwrap();
+    assert_eq!(&reparsed, lsd);
+}
+
+#[test]
+fn empty_level() {
+    round_trip(&LSD::Level(Level::default()));
+}
+
+#[test]
+fn empty_list() {
+    round_trip(&LSD::List(List::default()));
+}
+
+#[test]
+fn basic() {
+    round_trip(&LSD::Level(Level::from([
+        (
+            "a".to_string(),
+            LSD::Value("10".to_string()),
+        ),
+        (
+            "b needs quoting".to_string(),
+            LSD::Value("has \"quotes\" and a\ttab".to_string()),
+        ),
+        (
+            "c".to_string(),
+            LSD::Value("test string\nand spaces".to_string()),
+        ),
+    ])));
+}
+
+#[test]
+fn nested_level() {
+    round_trip(&LSD::Level(Level::from([
+        (
+            "a".to_string(),
+            LSD::Level(Level::from([(
+                "a".to_string(),
+                LSD::Value("10".to_string()),
+            )])),
+        ),
+        (
+            "b".to_string(),
+            LSD::Level(Level::default()),
+        ),
+    ])));
+}
+
+#[test]
+fn nested_list() {
+    round_trip(&LSD::Level(Level::from([(
+        "a".to_string(),
+        LSD::List(List::from([
+            LSD::Value("1".to_string()),
+            LSD::Level(Level::default()),
+            LSD::List(List::default()),
+        ])),
+    )])));
+}
+
+/// Parses `text`, then checks that writing and reparsing the result
+/// round-trips to the same tree - a property test complementing the
+/// hand-built trees above, covering whatever real escaping/nesting the
+/// fixture text itself exercises.
+fn round_trip_fixture(text: &str) {
+    let lsd = LSD::parse(Cursor::new(text)).unwrap();
+    round_trip(&lsd);
+}
+
+#[test]
+fn round_trip_basic_fixture() {
+    round_trip_fixture(
+        r#"
+            a 10 # comment
+            b 20
+            c a  "test string\nand spaces"  b
+            d 'also"string'
+            glued" key" test
+        "#,
+    );
+}
+
+#[test]
+fn round_trip_nested_level_fixture() {
+    round_trip_fixture(
+        r#"
+            a {
+                a 10
+            }
+            b{}
+            c{ c 30 }
+            d{ d 40
+               2 50 }
+        "#,
+    );
+}
+
+#[test]
+fn round_trip_nested_list_fixture() {
+    round_trip_fixture(
+        r#"
+            a [
+                a 10
+            ]
+            b[]
+            c[ 1 2 {} 3 4 ]
+            d[ 1 2
+               3 4 ]
+            e[ 1.2 ]
+        "#,
+    );
+}
+
+#[test]
+fn key_with_dot_is_requoted() {
+    let lsd = LSD::Level(Level::from([(
+        "a.b".to_string(),
+        LSD::Value("10".to_string()),
+    )]));
+
+    let written = lsd.to_string_pretty(4);
+    assert!(written.contains("\"a.b\""));
+
+    round_trip(&lsd);
+}